@@ -1,43 +1,64 @@
+//! Path finding over petgraph graphs, generic over the cost type. None of it is specific to the
+//! name selection screen.
+
 use petgraph::algo::Measure;
-use petgraph::visit::IntoNodeIdentifiers;
+use petgraph::visit::{IntoNeighbors, IntoNodeIdentifiers};
 use petgraph::visit::{EdgeRef, IntoEdges, Visitable};
 
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry::Occupied;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::Sub;
+
+mod pareto;
+pub use pareto::{pareto_covering_paths, pareto_dijkstra, pareto_dijkstra_nearest, Dominance};
 
 mod utils;
-use utils::{MinScored, PathTracker};
+pub(crate) use utils::MinScored;
+use utils::PathTracker;
+
+pub type PathDescriptions<N, K> = HashMap<N, Option<(K, Vec<N>)>>;
+pub type GroupedPaths<Q, N, K> = HashMap<Q, (K, Vec<N>)>;
+pub type PredecessorTree<N, K> = HashMap<N, (K, Option<N>)>;
+
+// The cost of every node reached, and the predecessor of each on its cheapest path.
+type SearchResult<N, K> = (HashMap<N, K>, HashMap<N, N>);
 
-pub fn dijkstra<G, F, K>(
+// Dijkstra from every one of the `starts` at once, until `is_done` accepts a node about to be
+// expanded, given along with its cost.
+fn search<G, D, F, T, K>(
     graph: G,
-    start: G::NodeId,
-    goals: Vec<G::NodeId>,
+    starts: &[G::NodeId],
+    mut is_done: D,
     edge_cost: F,
-) -> HashMap<G::NodeId, Option<(K, Vec<G::NodeId>)>>
+    tie_break: T,
+) -> SearchResult<G::NodeId, K>
 where
     G: IntoEdges + Visitable,
     G::NodeId: Eq + Hash,
+    D: FnMut(G::NodeId, K) -> bool,
     F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
     K: Measure + Copy,
 {
     // consider implementing a fibonacci heap for even faster times
     let mut next_nodes = BinaryHeap::new();
     let mut scores = HashMap::new();
     let mut predecessors = HashMap::new();
-    let mut remaining_goals: HashSet<_> = goals.iter().cloned().collect();
 
-    next_nodes.push(MinScored(start, K::default()));
-    scores.insert(start, K::default());
+    for &start in starts {
+        next_nodes.push(MinScored(start, K::default()));
+        scores.insert(start, K::default());
+    }
 
     while let Some(MinScored(current, score_when_queued)) = next_nodes.pop() {
         if score_when_queued > scores[&current] {
             continue; // already visited
         }
 
-        remaining_goals.remove(&current);
-        if remaining_goals.is_empty() {
+        if is_done(current, score_when_queued) {
             break;
         }
 
@@ -45,7 +66,12 @@ where
             let target = edge.target();
             let score_from_current = scores[&current] + edge_cost(edge);
             match scores.entry(target) {
-                Occupied(score_entry) if score_from_current >= *score_entry.get() => {}
+                Occupied(score_entry) if score_from_current > *score_entry.get() => {}
+                Occupied(score_entry) if score_from_current == *score_entry.get() => {
+                    if breaks_tie(&predecessors, current, target, |n| n, &tie_break) {
+                        predecessors.insert(target, current);
+                    }
+                }
                 score_entry => {
                     *score_entry.or_default() = score_from_current;
                     predecessors.insert(target, current);
@@ -55,28 +81,290 @@ where
         }
     }
 
-    let make_path = |goal: G::NodeId| {
-        let mut path: Vec<_> = [goal].into();
-        while let Some(&pred) = predecessors.get(path.last().unwrap()) {
-            path.push(pred);
+    (scores, predecessors)
+}
+
+/// Finds the cheapest path from `start` to each of the `goals`, stopping as soon as all of them
+/// are reached. Goals that can't be reached are described by `None`.
+///
+/// Paths of equal cost are told apart by `tie_break`, which is given two paths to the same node
+/// and returns `Ordering::Less` when the first one should be kept. Appending the same nodes to
+/// both paths must not change the verdict, which holds for any lexicographic order. Use
+/// `|_, _| Ordering::Equal` to keep whichever path is found first.
+pub fn dijkstra<G, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    goals: Vec<G::NodeId>,
+    edge_cost: F,
+    tie_break: T,
+) -> PathDescriptions<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy,
+{
+    multi_source_dijkstra(graph, &[start], goals, edge_cost, tie_break)
+}
+
+/// Same as `dijkstra`, but paths may start from any of the `starts`, whichever is cheapest.
+pub fn multi_source_dijkstra<G, F, T, K>(
+    graph: G,
+    starts: &[G::NodeId],
+    goals: Vec<G::NodeId>,
+    edge_cost: F,
+    tie_break: T,
+) -> PathDescriptions<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy,
+{
+    interruptible_dijkstra(graph, starts, goals, edge_cost, tie_break, || false).unwrap()
+}
+
+/// Same as `multi_source_dijkstra`, but gives up and returns `None` as soon as `should_stop`
+/// returns true, which is checked before expanding each node.
+pub fn interruptible_dijkstra<G, F, T, S, K>(
+    graph: G,
+    starts: &[G::NodeId],
+    goals: Vec<G::NodeId>,
+    edge_cost: F,
+    tie_break: T,
+    should_stop: S,
+) -> Option<PathDescriptions<G::NodeId, K>>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    S: Fn() -> bool,
+    K: Measure + Copy,
+{
+    let mut remaining_goals: HashSet<_> = goals.iter().cloned().collect();
+    let mut stopped = false;
+    let is_done = |current, _| {
+        if should_stop() {
+            stopped = true;
+            return true;
         }
-        path.reverse();
-        return path;
+        remaining_goals.remove(&current);
+        remaining_goals.is_empty()
     };
+    let (scores, predecessors) = search(graph, starts, is_done, edge_cost, tie_break);
+    if stopped {
+        return None;
+    }
 
-    goals
+    let descriptions = goals
         .into_iter()
         .map(|g| {
-            (
-                g,
-                scores
-                    .get(&g)
-                    .and_then(|score| Some((*score, make_path(g)))),
-            )
+            let desc = scores
+                .get(&g)
+                .map(|score| (*score, recreate_path(&predecessors, g)));
+            (g, desc)
         })
+        .collect();
+    Some(descriptions)
+}
+
+/// Same as `interruptible_dijkstra`, but each goal is met by reaching any of the nodes `goal_of`
+/// maps to it, such as the states of a search keeping track of more than the node. Returns the
+/// cheapest path to each goal that can be met, the nodes of a goal reached at the same cost being
+/// told apart by `tie_break` as well. The search stops once every goal is met, whatever nodes of
+/// it are left.
+pub fn grouped_dijkstra<G, Q, M, F, T, S, K>(
+    graph: G,
+    starts: &[G::NodeId],
+    goals: &[Q],
+    goal_of: M,
+    edge_cost: F,
+    tie_break: T,
+    should_stop: S,
+) -> Option<GroupedPaths<Q, G::NodeId, K>>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    Q: Eq + Hash + Copy,
+    M: Fn(G::NodeId) -> Option<Q>,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    S: Fn() -> bool,
+    K: Measure + Copy,
+{
+    let mut remaining_goals: HashSet<_> = goals.iter().cloned().collect();
+    // the nodes reaching each goal at the cheapest cost, which may still be joined by others
+    // until a costlier node is expanded
+    let mut reached: HashMap<Q, (K, Vec<G::NodeId>)> = HashMap::new();
+    let mut pending_goals: Vec<Q> = Vec::new();
+    let mut stopped = false;
+    let is_done = |current, score| {
+        if should_stop() {
+            stopped = true;
+            return true;
+        }
+        pending_goals.retain(|goal| {
+            let met = reached[goal].0 < score;
+            if met {
+                remaining_goals.remove(goal);
+            }
+            !met
+        });
+        if let Some(goal) = goal_of(current).filter(|goal| remaining_goals.contains(goal)) {
+            let (_, nodes) = reached.entry(goal).or_insert_with(|| {
+                pending_goals.push(goal);
+                (score, Vec::new())
+            });
+            nodes.push(current);
+        }
+        remaining_goals.is_empty()
+    };
+    let (_, predecessors) = search(graph, starts, is_done, edge_cost, &tie_break);
+    if stopped {
+        return None;
+    }
+
+    let descriptions = reached
+        .into_iter()
+        .map(|(goal, (score, nodes))| {
+            let path = nodes
+                .into_iter()
+                .map(|node| recreate_path(&predecessors, node))
+                .min_by(|a, b| tie_break(a, b))
+                .unwrap();
+            (goal, (score, path))
+        })
+        .collect();
+    Some(descriptions)
+}
+
+/// Runs `dijkstra` to completion and returns, for every node reachable from `start`, the cost of
+/// the cheapest path to it along with the node before it on that path. The path to any node is
+/// found by following predecessors back to `start`, which has none.
+pub fn predecessor_tree<G, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    edge_cost: F,
+    tie_break: T,
+) -> PredecessorTree<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy,
+{
+    let (scores, predecessors) = search(graph, &[start], |_, _| false, edge_cost, tie_break);
+    scores
+        .into_iter()
+        .map(|(node, score)| (node, (score, predecessors.get(&node).cloned())))
         .collect()
 }
 
+/// Same as `dijkstra`, but stops at the nearest node accepted by `is_goal`. Among nodes that are
+/// as near, the path preferred by `tie_break` is returned.
+pub fn dijkstra_nearest<G, P, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    is_goal: P,
+    edge_cost: F,
+    tie_break: T,
+) -> Option<(K, Vec<G::NodeId>)>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    P: Fn(G::NodeId) -> bool,
+    F: Fn(G::EdgeRef) -> K,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy,
+{
+    let mut next_nodes = BinaryHeap::new();
+    let mut scores = HashMap::new();
+    let mut predecessors = HashMap::new();
+    let mut nearest: Option<(K, Vec<G::NodeId>)> = None;
+
+    next_nodes.push(MinScored(start, K::default()));
+    scores.insert(start, K::default());
+
+    while let Some(MinScored(current, score_when_queued)) = next_nodes.pop() {
+        if score_when_queued > scores[&current] {
+            continue; // already visited
+        }
+
+        if let Some((nearest_score, _)) = nearest {
+            if score_when_queued > nearest_score {
+                break;
+            }
+        }
+
+        if is_goal(current) {
+            let path = recreate_path(&predecessors, current);
+            if nearest
+                .as_ref()
+                .is_none_or(|(_, nearest_path)| tie_break(&path, nearest_path) == Ordering::Less)
+            {
+                nearest = Some((score_when_queued, path));
+            }
+            continue;
+        }
+
+        for edge in graph.edges(current) {
+            let target = edge.target();
+            let score_from_current = scores[&current] + edge_cost(edge);
+            match scores.entry(target) {
+                Occupied(score_entry) if score_from_current > *score_entry.get() => {}
+                Occupied(score_entry) if score_from_current == *score_entry.get() => {
+                    if breaks_tie(&predecessors, current, target, |n| n, &tie_break) {
+                        predecessors.insert(target, current);
+                    }
+                }
+                score_entry => {
+                    *score_entry.or_default() = score_from_current;
+                    predecessors.insert(target, current);
+                    next_nodes.push(MinScored(target, score_from_current))
+                }
+            }
+        }
+    }
+
+    nearest
+}
+
+/// Every node reachable from `start` in at most `max_steps` edges, or in any number of them when
+/// it is `None`, entering only nodes accepted by `can_enter`. `start` itself is always included.
+pub fn reachable<G, P>(
+    graph: G,
+    start: G::NodeId,
+    max_steps: Option<usize>,
+    can_enter: P,
+) -> HashSet<G::NodeId>
+where
+    G: IntoNeighbors,
+    G::NodeId: Eq + Hash,
+    P: Fn(G::NodeId) -> bool,
+{
+    let mut reached: HashSet<_> = std::iter::once(start).collect();
+    let mut next_nodes: VecDeque<_> = std::iter::once((start, 0)).collect();
+
+    while let Some((current, steps)) = next_nodes.pop_front() {
+        if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+            continue;
+        }
+        for next in graph.neighbors(current) {
+            if can_enter(next) && reached.insert(next) {
+                next_nodes.push_back((next, steps + 1));
+            }
+        }
+    }
+
+    reached
+}
+
+/// Finds the cheapest path from `start` visiting every node of the graph exactly once, by
+/// exhaustive best-first search. Only practical on small graphs, such as one between goals.
 pub fn shortest_hamiltonian_path<G, F, K>(
     graph: G,
     start: G::NodeId,
@@ -113,3 +401,295 @@ where
 
     None
 }
+
+/// Finds the cheapest path covering every `required` bit, where `start_cover` is covered from the
+/// start and taking an edge covers the bits returned by `edge_cover`. Nodes may be revisited, and
+/// the path may keep going once complete if the `reward` of the extra bits it covers outweighs the
+/// cost of covering them. Rewards must never be negative. Ending on a node adds its `end_cost`,
+/// and the path can't end on nodes without one. Ties are broken as in `dijkstra`.
+#[allow(clippy::too_many_arguments)]
+pub fn shortest_covering_path<G, F, C, R, E, T, K>(
+    graph: G,
+    start: G::NodeId,
+    start_cover: u64,
+    required: u64,
+    edge_cost: F,
+    edge_cover: C,
+    reward: R,
+    end_cost: E,
+    tie_break: T,
+) -> Option<(Vec<G::NodeId>, K)>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    C: Fn(G::EdgeRef) -> u64,
+    R: Fn(u64) -> K,
+    E: Fn(G::NodeId) -> Option<K>,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy + Sub<Output = K>,
+{
+    anytime_covering_path(
+        graph,
+        start,
+        start_cover,
+        required,
+        edge_cost,
+        edge_cover,
+        reward,
+        end_cost,
+        tie_break,
+        None,
+        |_| false,
+    )
+    .map(|(path, cost, _)| (path, cost))
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SearchProgress<K> {
+    pub expanded: usize,
+    pub best_cost: Option<K>,
+}
+
+// How many states are expanded between two calls to `should_stop`, when the best path doesn't
+// change in the meantime.
+const PROGRESS_INTERVAL: usize = 4096;
+
+/// Same as `shortest_covering_path`, but the search can be interrupted. `should_stop` is given the
+/// progress made regularly and whenever a better path is found, and the search stops as soon as
+/// it returns true. The `incumbent`, a complete path along with its cost and covered bits, is
+/// returned when nothing better is found in time. Along with the best path and its cost, returns
+/// whether the path is known to be the cheapest.
+#[allow(clippy::too_many_arguments)]
+pub fn anytime_covering_path<G, F, C, R, E, T, S, K>(
+    graph: G,
+    start: G::NodeId,
+    start_cover: u64,
+    required: u64,
+    edge_cost: F,
+    edge_cover: C,
+    reward: R,
+    end_cost: E,
+    tie_break: T,
+    incumbent: Option<(Vec<G::NodeId>, K, u64)>,
+    mut should_stop: S,
+) -> Option<(Vec<G::NodeId>, K, bool)>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    C: Fn(G::EdgeRef) -> u64,
+    R: Fn(u64) -> K,
+    E: Fn(G::NodeId) -> Option<K>,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    S: FnMut(&SearchProgress<K>) -> bool,
+    K: Measure + Copy + Sub<Output = K>,
+{
+    let max_reward = reward(!0);
+    let mut next_states = BinaryHeap::new();
+    let mut scores = HashMap::new();
+    let mut predecessors = HashMap::new();
+    // the best path so far, with its cost and its score once rewards are deducted
+    let mut best = incumbent.map(|(path, cost, covered)| (path, cost, cost - reward(covered)));
+    let mut progress = SearchProgress {
+        expanded: 0,
+        best_cost: best.as_ref().map(|&(_, cost, _)| cost),
+    };
+    let mut stopped = false;
+
+    next_states.push(MinScored((start, start_cover), K::default()));
+    scores.insert((start, start_cover), K::default());
+
+    while let Some(MinScored(current, score_when_queued)) = next_states.pop() {
+        if score_when_queued > scores[&current] {
+            continue; // already visited
+        }
+
+        // no state left in the queue can make up for its cost with rewards anymore
+        if let Some((_, _, best_score)) = best {
+            if score_when_queued >= best_score + max_reward {
+                break;
+            }
+        }
+
+        if progress.expanded % PROGRESS_INTERVAL == 0 && should_stop(&progress) {
+            stopped = true;
+            break;
+        }
+        progress.expanded += 1;
+
+        let (node, covered) = current;
+        if covered & required == required {
+            if let Some(end_cost) = end_cost(node) {
+                let cost = score_when_queued + end_cost;
+                let score = cost - reward(covered);
+                let path = || -> Vec<_> {
+                    let path = recreate_path(&predecessors, current);
+                    path.into_iter().map(|(node, _)| node).collect()
+                };
+                let improves = match best {
+                    None => Some(path()),
+                    Some((_, _, best_score)) if score < best_score => Some(path()),
+                    Some((ref best_path, _, best_score)) if score == best_score => {
+                        Some(path()).filter(|path| tie_break(path, best_path) == Ordering::Less)
+                    }
+                    Some(_) => None,
+                };
+                if let Some(path) = improves {
+                    best = Some((path, cost, score));
+                    progress.best_cost = Some(cost);
+                    if should_stop(&progress) {
+                        stopped = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        for edge in graph.edges(node) {
+            let target = (edge.target(), covered | edge_cover(edge));
+            let score_from_current = scores[&current] + edge_cost(edge);
+            match scores.entry(target) {
+                Occupied(score_entry) if score_from_current > *score_entry.get() => {}
+                Occupied(score_entry) if score_from_current == *score_entry.get() => {
+                    if breaks_tie(&predecessors, current, target, |s| s.0, &tie_break) {
+                        predecessors.insert(target, current);
+                    }
+                }
+                score_entry => {
+                    *score_entry.or_default() = score_from_current;
+                    predecessors.insert(target, current);
+                    next_states.push(MinScored(target, score_from_current))
+                }
+            }
+        }
+    }
+
+    best.map(|(path, cost, _)| (path, cost, !stopped))
+}
+
+fn recreate_path<S: Copy + Eq + Hash>(predecessors: &HashMap<S, S>, last: S) -> Vec<S> {
+    let mut path = vec![last];
+    while let Some(&pred) = predecessors.get(path.last().unwrap()) {
+        path.push(pred);
+    }
+    path.reverse();
+    path
+}
+
+// Whether the path going through `current` to reach `target` should replace the known one, when
+// both cost the same. Search states are turned into graph nodes by `node`.
+fn breaks_tie<S, N, P, T>(
+    predecessors: &HashMap<S, S>,
+    current: S,
+    target: S,
+    node: P,
+    tie_break: &T,
+) -> bool
+where
+    S: Copy + Eq + Hash,
+    P: Fn(S) -> N,
+    T: Fn(&[N], &[N]) -> Ordering,
+{
+    let path_to_current = recreate_path(predecessors, current);
+    if path_to_current.contains(&target) {
+        return false; // only happens through edges that cost nothing, or infinitely much
+    }
+    let known_path: Vec<_> = recreate_path(predecessors, target)
+        .into_iter()
+        .map(&node)
+        .collect();
+    let path_through_current: Vec<_> = path_to_current
+        .into_iter()
+        .chain(std::iter::once(target))
+        .map(&node)
+        .collect();
+    tie_break(&path_through_current, &known_path) == Ordering::Less
+}
+
+/// Explores every path of cost up to `limit`, like `shortest_covering_path`, and returns the
+/// cheapest path for each improvement of the `value` of the bits it covers. The returned paths are
+/// sorted by cost, each one worth strictly more than the previous.
+#[allow(clippy::too_many_arguments)]
+pub fn covering_frontier<G, F, C, V, E, K, W>(
+    graph: G,
+    start: G::NodeId,
+    start_cover: u64,
+    limit: K,
+    edge_cost: F,
+    edge_cover: C,
+    value: V,
+    end_cost: E,
+) -> Vec<(Vec<G::NodeId>, K, u64)>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> K,
+    C: Fn(G::EdgeRef) -> u64,
+    V: Fn(u64) -> W,
+    E: Fn(G::NodeId) -> Option<K>,
+    K: Measure + Copy,
+    W: PartialOrd + Copy,
+{
+    let mut next_states = BinaryHeap::new();
+    let mut scores = HashMap::new();
+    let mut predecessors = HashMap::new();
+    let mut candidates = Vec::new();
+
+    next_states.push(MinScored((start, start_cover), K::default()));
+    scores.insert((start, start_cover), K::default());
+
+    while let Some(MinScored(current, score_when_queued)) = next_states.pop() {
+        if score_when_queued > scores[&current] {
+            continue; // already visited
+        }
+
+        let (node, covered) = current;
+        if let Some(end_cost) = end_cost(node) {
+            let total_cost = score_when_queued + end_cost;
+            if total_cost <= limit {
+                candidates.push((current, total_cost, value(covered)));
+            }
+        }
+
+        for edge in graph.edges(node) {
+            let target = (edge.target(), covered | edge_cover(edge));
+            let score_from_current = scores[&current] + edge_cost(edge);
+            if score_from_current > limit {
+                continue;
+            }
+            match scores.entry(target) {
+                Occupied(score_entry) if score_from_current >= *score_entry.get() => {}
+                score_entry => {
+                    *score_entry.or_default() = score_from_current;
+                    predecessors.insert(target, current);
+                    next_states.push(MinScored(target, score_from_current))
+                }
+            }
+        }
+    }
+
+    // Adding the end cost may shuffle the order in which states were reached, so the frontier can
+    // only be extracted once every candidate is known.
+    candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+
+    let mut best_value: Option<W> = None;
+    candidates
+        .into_iter()
+        .filter(|&(_, _, value)| {
+            let improves = best_value.is_none_or(|best_value| value > best_value);
+            if improves {
+                best_value = Some(value);
+            }
+            improves
+        })
+        .map(|(last, total_cost, _)| {
+            let path = recreate_path(&predecessors, last);
+            (
+                path.into_iter().map(|(node, _)| node).collect(),
+                total_cost,
+                last.1,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,329 @@
+use petgraph::algo::Measure;
+use petgraph::visit::{EdgeRef, IntoEdges, Visitable};
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use super::utils::MinScored;
+
+/// Multi-objective costs. `dominates` must return true when `self` is at least as good as `other`
+/// on every objective and strictly better on one. The `PartialOrd` of the cost must be a linear
+/// extension of that relation (a lexicographic order is), so that labels are settled before any
+/// label they dominate.
+pub trait Dominance {
+    fn dominates(&self, other: &Self) -> bool;
+}
+
+pub type ParetoPaths<N, K> = HashMap<N, Vec<(K, Vec<N>)>>;
+pub type ParetoCoveringPaths<N, K> = Vec<(Vec<(N, usize)>, usize, K)>;
+
+struct Label<N, K> {
+    node: N,
+    cost: K,
+    predecessor: Option<usize>,
+    option: usize,
+}
+
+fn recreate_labels<N: Copy, K>(labels: &[Label<N, K>], last: usize) -> Vec<(N, usize)> {
+    let mut path = vec![(labels[last].node, labels[last].option)];
+    let mut cursor = last;
+    while let Some(predecessor) = labels[cursor].predecessor {
+        path.push((labels[predecessor].node, labels[predecessor].option));
+        cursor = predecessor;
+    }
+    path.reverse();
+    path
+}
+
+// Only the first of the labels of equal cost on the same node is expanded. When `current` comes
+// through a path `tie_break` prefers, the settled label takes its predecessor and option, so that
+// every path going through it follows the preferred one instead.
+fn break_tie<N, K, T>(labels: &mut [Label<N, K>], settled: &[usize], current: usize, tie_break: T)
+where
+    N: Copy,
+    K: PartialEq,
+    T: Fn(&[(N, usize)], &[(N, usize)]) -> Ordering,
+{
+    let equal = settled
+        .iter()
+        .find(|&&label| labels[label].cost == labels[current].cost);
+    if let Some(&label) = equal {
+        let path = recreate_labels(labels, current);
+        if tie_break(&path, &recreate_labels(labels, label)) == Ordering::Less {
+            labels[label].predecessor = labels[current].predecessor;
+            labels[label].option = labels[current].option;
+        }
+    }
+}
+
+// Returns true, and remembers the cost, if it isn't dominated by (or equal to) one already known.
+fn insert_if_nondominated<K: Dominance + PartialEq + Copy>(front: &mut Vec<K>, cost: K) -> bool {
+    if front
+        .iter()
+        .any(|known| known.dominates(&cost) || *known == cost)
+    {
+        return false;
+    }
+    front.retain(|known| !cost.dominates(known));
+    front.push(cost);
+    true
+}
+
+// Label-setting search for every Pareto-optimal path from `start` to each goal accepted by
+// `is_goal`. Edges for which `edge_cost` returns `None` can't be taken. When the goals are listed in
+// `each_goal`, a front is kept for each of them, and labels are dropped once every goal has a path
+// dominating them. Otherwise goals are interchangeable, and a single front is kept over all of
+// them, which lets the search stop much earlier. Paths to each goal are sorted by cost, then by
+// `tie_break`.
+fn pareto_search<G, P, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    is_goal: P,
+    each_goal: Option<&[G::NodeId]>,
+    edge_cost: F,
+    tie_break: T,
+) -> ParetoPaths<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    P: Fn(G::NodeId) -> bool,
+    F: Fn(G::EdgeRef) -> Option<K>,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy + Dominance,
+{
+    let nodes =
+        |path: &[(G::NodeId, usize)]| path.iter().map(|&(node, _)| node).collect::<Vec<_>>();
+    let label_tie_break =
+        |a: &[(G::NodeId, usize)], b: &[(G::NodeId, usize)]| tie_break(&nodes(a), &nodes(b));
+
+    let mut labels = vec![Label {
+        node: start,
+        cost: K::default(),
+        predecessor: None,
+        option: 0,
+    }];
+    let mut next_labels = BinaryHeap::new();
+    let mut fronts: HashMap<G::NodeId, Vec<K>> = HashMap::new();
+    let mut settled: HashMap<G::NodeId, Vec<usize>> = HashMap::new();
+    let mut goal_front = Vec::new();
+    let mut goal_labels = Vec::new();
+
+    next_labels.push(MinScored(0, K::default()));
+
+    let goals_are_interchangeable = each_goal.is_none();
+    while let Some(MinScored(current, cost)) = next_labels.pop() {
+        let node = labels[current].node;
+        let dominated_at = |goal| {
+            fronts
+                .get(goal)
+                .is_some_and(|front: &Vec<K>| front.iter().any(|k| k.dominates(&cost)))
+        };
+        let dominated = match each_goal {
+            Some(goals) => goals.iter().all(dominated_at),
+            None => goal_front.iter().any(|goal: &K| goal.dominates(&cost)),
+        };
+        if dominated {
+            continue; // any goal reached from here would be dominated
+        }
+        if !insert_if_nondominated(fronts.entry(node).or_default(), cost) {
+            break_tie(&mut labels, &settled[&node], current, label_tie_break);
+            continue;
+        }
+        settled.entry(node).or_default().push(current);
+
+        if is_goal(node) {
+            if goals_are_interchangeable {
+                insert_if_nondominated(&mut goal_front, cost);
+            }
+            goal_labels.push(current);
+        }
+
+        for edge in graph.edges(node) {
+            if let Some(edge_cost) = edge_cost(edge) {
+                let target_cost = cost + edge_cost;
+                let target_front = fronts.get(&edge.target());
+                if target_front.is_none_or(|front| !front.iter().any(|k| k.dominates(&target_cost)))
+                {
+                    labels.push(Label {
+                        node: edge.target(),
+                        cost: target_cost,
+                        predecessor: Some(current),
+                        option: 0,
+                    });
+                    next_labels.push(MinScored(labels.len() - 1, target_cost));
+                }
+            }
+        }
+    }
+
+    let mut paths: HashMap<_, Vec<_>> = HashMap::new();
+    for goal_label in goal_labels {
+        let label = &labels[goal_label];
+        // a later goal label may still have removed this one from the goal front
+        if !goals_are_interchangeable || goal_front.contains(&label.cost) {
+            let path = recreate_labels(&labels, goal_label);
+            paths
+                .entry(label.node)
+                .or_default()
+                .push((label.cost, nodes(&path)));
+        }
+    }
+    for goal_paths in paths.values_mut() {
+        goal_paths.sort_by(|(a_cost, a_path), (b_cost, b_path)| {
+            a_cost
+                .partial_cmp(b_cost)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| tie_break(a_path, b_path))
+        });
+    }
+    paths
+}
+
+/// Every Pareto-optimal path from `start` to each of the `goals`, sorted by cost. Among paths of
+/// equal cost, the one kept is the least according to `tie_break`.
+pub fn pareto_dijkstra<G, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    goals: &[G::NodeId],
+    edge_cost: F,
+    tie_break: T,
+) -> ParetoPaths<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    F: Fn(G::EdgeRef) -> Option<K>,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy + Dominance,
+{
+    pareto_search(
+        graph,
+        start,
+        |node| goals.contains(&node),
+        Some(goals),
+        edge_cost,
+        tie_break,
+    )
+}
+
+/// Every Pareto-optimal path from `start` to any node accepted by `is_goal`, sorted by cost, then
+/// by `tie_break` among paths of equal cost to different nodes.
+pub fn pareto_dijkstra_nearest<G, P, F, T, K>(
+    graph: G,
+    start: G::NodeId,
+    is_goal: P,
+    edge_cost: F,
+    tie_break: T,
+) -> Vec<(K, Vec<G::NodeId>)>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    P: Fn(G::NodeId) -> bool,
+    F: Fn(G::EdgeRef) -> Option<K>,
+    T: Fn(&[G::NodeId], &[G::NodeId]) -> Ordering,
+    K: Measure + Copy + Dominance,
+{
+    let mut paths: Vec<_> = pareto_search(graph, start, is_goal, None, edge_cost, &tie_break)
+        .into_values()
+        .flatten()
+        .collect();
+    paths.sort_by(|(a_cost, a_path), (b_cost, b_path)| {
+        a_cost
+            .partial_cmp(b_cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| tie_break(a_path, b_path))
+    });
+    paths
+}
+
+/// Multi-objective version of `shortest_covering_path`, without rewards. Each edge can be taken in
+/// any of the ways listed by `edge_options`, each with its own cost and covered bits, and ending on
+/// a node can be done in any of the ways listed by `end_options`. Returns the Pareto front of
+/// complete paths, each as the list of nodes along with the option taken to reach them, the option
+/// taken to end the path, and the total cost. Paths of equal cost reaching the same node with the
+/// same bits covered are told apart by `tie_break`, but complete paths of equal cost are all
+/// returned, since the option taken to end them is left for the caller to compare.
+pub fn pareto_covering_paths<G, O, E, T, K>(
+    graph: G,
+    start: G::NodeId,
+    start_cover: u64,
+    required: u64,
+    edge_options: O,
+    end_options: E,
+    tie_break: T,
+) -> ParetoCoveringPaths<G::NodeId, K>
+where
+    G: IntoEdges + Visitable,
+    G::NodeId: Eq + Hash,
+    O: Fn(G::EdgeRef) -> Vec<(K, u64)>,
+    E: Fn(G::NodeId) -> Vec<K>,
+    T: Fn(&[(G::NodeId, usize)], &[(G::NodeId, usize)]) -> Ordering,
+    K: Measure + Copy + Dominance,
+{
+    let mut labels = vec![Label {
+        node: start,
+        cost: K::default(),
+        predecessor: None,
+        option: 0,
+    }];
+    let mut covers = vec![start_cover];
+    let mut next_labels = BinaryHeap::new();
+    let mut fronts: HashMap<(G::NodeId, u64), Vec<K>> = HashMap::new();
+    let mut settled: HashMap<(G::NodeId, u64), Vec<usize>> = HashMap::new();
+    let mut complete_front = Vec::new();
+    let mut complete_labels = Vec::new();
+
+    next_labels.push(MinScored(0, K::default()));
+
+    while let Some(MinScored(current, cost)) = next_labels.pop() {
+        let (node, covered) = (labels[current].node, covers[current]);
+        if complete_front
+            .iter()
+            .any(|complete: &K| complete.dominates(&cost))
+        {
+            continue; // any path completed from here would be dominated
+        }
+        if !insert_if_nondominated(fronts.entry((node, covered)).or_default(), cost) {
+            break_tie(&mut labels, &settled[&(node, covered)], current, &tie_break);
+            continue;
+        }
+        settled.entry((node, covered)).or_default().push(current);
+
+        if covered & required == required {
+            for (end_option, end_cost) in end_options(node).into_iter().enumerate() {
+                let total_cost = cost + end_cost;
+                if complete_front.contains(&total_cost)
+                    || insert_if_nondominated(&mut complete_front, total_cost)
+                {
+                    complete_labels.push((current, end_option, total_cost));
+                }
+            }
+        }
+
+        for edge in graph.edges(node) {
+            for (option, (edge_cost, edge_cover)) in edge_options(edge).into_iter().enumerate() {
+                let target_cost = cost + edge_cost;
+                let target_front = fronts.get(&(edge.target(), covered | edge_cover));
+                if target_front.is_none_or(|front| !front.iter().any(|k| k.dominates(&target_cost)))
+                {
+                    labels.push(Label {
+                        node: edge.target(),
+                        cost: target_cost,
+                        predecessor: Some(current),
+                        option,
+                    });
+                    covers.push(covered | edge_cover);
+                    next_labels.push(MinScored(labels.len() - 1, target_cost));
+                }
+            }
+        }
+    }
+
+    complete_labels
+        .into_iter()
+        .filter(|(_, _, total_cost)| complete_front.contains(total_cost))
+        .map(|(last, end_option, total_cost)| {
+            (recreate_labels(&labels, last), end_option, total_cost)
+        })
+        .collect()
+}
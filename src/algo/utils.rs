@@ -31,7 +31,7 @@ impl<T, K: PartialOrd> Ord for MinScored<T, K> {
     fn cmp(&self, other: &MinScored<T, K>) -> Ordering {
         let left = &self.1;
         let right = &other.1;
-        match left.partial_cmp(&right) {
+        match left.partial_cmp(right) {
             Some(ordering) => ordering.reverse(),
             None if left.ne(right) && right.ne(left) => Ordering::Equal,
             None if left.ne(left) => Ordering::Less,
@@ -77,7 +77,7 @@ where
         };
 
         self.nodes.push(Box::pin(node));
-        return node_ref;
+        node_ref
     }
 
     pub fn push_root(&mut self, root: T) -> PathTrackerNodeRef<T> {
@@ -113,6 +113,6 @@ where
             }
             cursor = predecessor;
         }
-        return cursor.id == id;
+        cursor.id == id
     }
 }
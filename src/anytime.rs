@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use petgraph::graphmap;
+use petgraph::visit::EdgeRef;
+
+use crate::algo;
+use crate::cost::CostModel;
+use crate::route::{goal_bits, model_goal_graph, required_bits, GoalGraph, RouteCost};
+use crate::tie_break::{tie_broken_edge_cost, DEFAULT_TIE_BREAKS};
+use crate::{CodeSegments, Directions, EndCondition, Goal};
+
+// How often progress is reported while the goal graph is being built.
+const PROGRESS_PERIOD: Duration = Duration::from_millis(100);
+
+/// Shared flag to stop a search from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Progress {
+    pub nodes_expanded: usize,
+    pub best_cost: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnytimeCode {
+    pub code_segments: CodeSegments,
+    pub cost: f32,
+    /// False when the search was stopped before it could prove that no cheaper code exists.
+    pub optimal: bool,
+}
+
+/// Same as `get_shortest_code`, but stops once the deadline is past or the token is cancelled,
+/// and then returns the best code found so far. A greedy code going to the nearest remaining
+/// required goal is found before searching, so there is usually something to return even when
+/// stopped right away. Nothing is returned when stopped before the goal graph is built, since no
+/// code is known yet. `on_progress` is called regularly and whenever a better code is found.
+#[allow(clippy::too_many_arguments)]
+pub fn get_shortest_code_anytime<P>(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+    deadline: Option<Instant>,
+    cancellation: &CancellationToken,
+    mut on_progress: P,
+) -> Option<AnytimeCode>
+where
+    P: FnMut(&Progress),
+{
+    let edge_cost = tie_broken_edge_cost(cost_model, &DEFAULT_TIE_BREAKS);
+    let is_stopped = || {
+        cancellation.is_cancelled() || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    };
+
+    // The goal graph is built on its own thread, so that progress can be reported from this one
+    // in the meantime.
+    let build_expanded = AtomicUsize::new(0);
+    let should_stop = || {
+        build_expanded.fetch_add(1, atomic::Ordering::Relaxed);
+        is_stopped()
+    };
+    let goal_graph = thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        scope.spawn(move || {
+            let goal_graph = model_goal_graph(
+                graph,
+                from_node,
+                goals,
+                end,
+                cost_model,
+                edge_cost,
+                &should_stop,
+            );
+            sender.send(goal_graph).ok();
+        });
+        loop {
+            match receiver.recv_timeout(PROGRESS_PERIOD) {
+                Ok(goal_graph) => return goal_graph,
+                Err(RecvTimeoutError::Timeout) => on_progress(&Progress {
+                    nodes_expanded: build_expanded.load(atomic::Ordering::Relaxed),
+                    best_cost: None,
+                }),
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    })?;
+    let build_expanded = build_expanded.into_inner();
+    let required = required_bits(goals);
+
+    algo::anytime_covering_path(
+        &goal_graph.graph,
+        from_node,
+        goal_bits(goals, from_node),
+        required,
+        |e| e.weight().0,
+        |e| e.weight().1,
+        |covered| goal_graph.reward(covered),
+        |node| goal_graph.end_cost(node),
+        |a, b| goal_graph.compare_goal_paths(a, b),
+        greedy_goal_path(
+            &goal_graph,
+            from_node,
+            goal_bits(goals, from_node),
+            required,
+        ),
+        |search_progress| {
+            on_progress(&Progress {
+                nodes_expanded: build_expanded + search_progress.expanded,
+                best_cost: search_progress.best_cost.map(|cost| cost.cost()),
+            });
+            is_stopped()
+        },
+    )
+    .map(|(goal_path, cost, optimal)| AnytimeCode {
+        code_segments: goal_graph.code_segments(graph, &goal_path),
+        cost: cost.cost() + goal_graph.boundary_transition_cost(&goal_path, cost_model),
+        optimal,
+    })
+}
+
+// Always heads for the nearest required goal not covered yet, then ends as the end condition
+// requires. Returns the path in the goal graph along with its cost and the goals it covers.
+fn greedy_goal_path<K: RouteCost>(
+    goal_graph: &GoalGraph<K>,
+    from_node: i32,
+    start_cover: u64,
+    required: u64,
+) -> Option<(Vec<i32>, K, u64)> {
+    let mut goal_path = vec![from_node];
+    let mut cost = K::default();
+    let mut covered = start_cover;
+
+    while covered & required != required {
+        let (_, to, &(w, cover)) = goal_graph
+            .graph
+            .edges(*goal_path.last().unwrap())
+            .filter(|(_, _, &(_, cover))| cover & required & !covered != 0)
+            .min_by(|(_, _, (a, _)), (_, _, (b, _))| a.partial_cmp(b).unwrap_or(Ordering::Equal))?;
+        goal_path.push(to);
+        cost = cost + w;
+        covered |= cover;
+    }
+
+    let end_cost = goal_graph.end_cost(*goal_path.last().unwrap())?;
+    Some((goal_path, cost + end_cost, covered))
+}
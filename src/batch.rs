@@ -0,0 +1,25 @@
+use petgraph::graphmap;
+
+use crate::cost::CostModel;
+use crate::utils::parallel_map;
+use crate::{get_shortest_code, Directions, EndCondition, Goal, ShortestCode};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteQuery {
+    pub from_node: i32,
+    pub goals: Vec<Goal>,
+    pub end: EndCondition,
+}
+
+/// Runs `get_shortest_code` for every query, sharing the graph between as many threads as there
+/// are cores. Each query is then searched on a single thread. The results are in the same order
+/// as the queries.
+pub fn get_shortest_codes(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    queries: &[RouteQuery],
+    cost_model: &dyn CostModel,
+) -> Vec<Option<ShortestCode>> {
+    parallel_map(queries, |query| {
+        get_shortest_code(graph, query.from_node, &query.goals, &query.end, cost_model)
+    })
+}
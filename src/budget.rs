@@ -0,0 +1,157 @@
+use std::ops::{Add, Sub};
+
+use petgraph::graphmap;
+use petgraph::visit::EdgeRef;
+
+use crate::algo;
+use crate::cost::{model_edge_cost, CostModel};
+use crate::route::{goal_bits, GoalGraph, RouteCost};
+use crate::{CodeSegments, Directions, EndCondition, Goal};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Budget {
+    /// Hard cap on the number of inputs in the code. Codes with as many inputs are told apart by
+    /// their cost.
+    Inputs(usize),
+    /// Hard cap on the total cost of the code, as given by the cost model.
+    Cost(f32),
+}
+
+#[derive(Clone, Debug)]
+pub struct BudgetedCode {
+    pub code_segments: CodeSegments,
+    pub inputs: usize,
+    pub cost: f32,
+    pub value: f32,
+}
+
+/// Finds the codes reaching the most valuable set of goals within the budget. None of the goals
+/// are mandatory here. The returned codes form a frontier sorted by the part of the budget they
+/// use: for any budget value, the best code is the last one that fits. Transitions between inputs
+/// are left out of costs, and every input is pressed rather than held. Nothing is returned for
+/// more than 64 goals.
+pub fn get_budgeted_codes(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+    budget: Budget,
+) -> Vec<BudgetedCode> {
+    let edge_cost = model_edge_cost(cost_model);
+    let frontier = match budget {
+        Budget::Inputs(max_inputs) => {
+            let input_cost = |e: (i32, i32, &Directions)| {
+                let cost = edge_cost(e);
+                InputsThenCost {
+                    inputs: if cost.is_infinite() {
+                        f32::INFINITY
+                    } else {
+                        1.0
+                    },
+                    cost,
+                }
+            };
+            let limit = InputsThenCost {
+                inputs: max_inputs as f32,
+                cost: f32::INFINITY,
+            };
+            budgeted_cursor_paths(graph, from_node, goals, end, input_cost, limit)
+        }
+        Budget::Cost(max_cost) => {
+            budgeted_cursor_paths(graph, from_node, goals, end, edge_cost, max_cost)
+        }
+    };
+
+    frontier
+        .into_iter()
+        .map(|(cursor_path, code_segments, value)| BudgetedCode {
+            code_segments,
+            inputs: cursor_path.len() - 1,
+            cost: cursor_path
+                .iter()
+                .zip(cursor_path.iter().skip(1))
+                .map(|(&from, &to)| edge_cost((from, to, graph.edge_weight(from, to).unwrap())))
+                .fold(0.0, |total, cost| total + cost),
+            value,
+        })
+        .collect()
+}
+
+// Counts inputs, telling codes with as many inputs apart by their cost.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+struct InputsThenCost {
+    inputs: f32,
+    cost: f32,
+}
+
+impl Add for InputsThenCost {
+    type Output = InputsThenCost;
+
+    fn add(self, other: InputsThenCost) -> InputsThenCost {
+        InputsThenCost {
+            inputs: self.inputs + other.inputs,
+            cost: self.cost + other.cost,
+        }
+    }
+}
+
+impl Sub for InputsThenCost {
+    type Output = InputsThenCost;
+
+    fn sub(self, other: InputsThenCost) -> InputsThenCost {
+        InputsThenCost {
+            inputs: self.inputs - other.inputs,
+            cost: self.cost - other.cost,
+        }
+    }
+}
+
+impl RouteCost for InputsThenCost {
+    fn from_cost(cost: f32) -> InputsThenCost {
+        InputsThenCost { inputs: 0.0, cost }
+    }
+
+    fn cost(&self) -> f32 {
+        self.cost
+    }
+}
+
+fn budgeted_cursor_paths<K, F>(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    edge_cost: F,
+    limit: K,
+) -> Vec<(Vec<i32>, CodeSegments, f32)>
+where
+    K: RouteCost,
+    F: Fn((i32, i32, &Directions)) -> K + Copy + Sync,
+{
+    let goal_graph = match GoalGraph::new(graph, from_node, goals, end, edge_cost, &|| false) {
+        Some(goal_graph) => goal_graph,
+        None => return Vec::new(),
+    };
+    let value = |covered| goal_graph.value(covered);
+
+    algo::covering_frontier(
+        &goal_graph.graph,
+        from_node,
+        goal_bits(goals, from_node),
+        limit,
+        |e| e.weight().0,
+        |e| e.weight().1,
+        value,
+        |node| goal_graph.end_cost(node),
+    )
+    .into_iter()
+    .map(|(goal_path, _, covered)| {
+        (
+            goal_graph.cursor_path(&goal_path),
+            goal_graph.code_segments(graph, &goal_path),
+            value(covered),
+        )
+    })
+    .collect()
+}
@@ -0,0 +1,150 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use petgraph::graphmap;
+
+use crate::{Directions, Hazard};
+
+/// Every input sequence ending on `end` and passing through exactly the `visited` indices (the
+/// starting index is only included when the cursor comes back to it) is collapsed into one entry,
+/// with `code` as an example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatalogEntry {
+    pub end: i32,
+    pub visited: BTreeSet<i32>,
+    pub code: Vec<Directions>,
+}
+
+/// The entries first achievable with each number of inputs, indexed by that number.
+pub type CodeCatalog = Vec<Vec<CatalogEntry>>;
+
+/// Which sequences ending on the same index are pruned, besides the ones visiting exactly the same
+/// indices in more inputs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VisitDominance {
+    /// Nothing else is pruned, so that every achievable visit-set is listed.
+    Exact,
+    /// Sequences visiting at least the indices of another one reached in as few inputs, since
+    /// every index visited writes to memory: anything with fewer side effects is kept.
+    FewerVisits,
+    /// Sequences visiting at most the indices of another one reached in as few inputs, since
+    /// anything the former can still achieve, the latter can achieve too.
+    MoreVisits,
+}
+
+// The visit-sets kept for one end index. Each set is listed under every index it contains, so that
+// the supersets of another one are found without going through all of them.
+#[derive(Default)]
+struct KnownSets {
+    sets: Vec<BTreeSet<i32>>,
+    containing: HashMap<i32, Vec<usize>>,
+    exact: HashSet<BTreeSet<i32>>,
+}
+
+impl KnownSets {
+    fn insert(&mut self, set: BTreeSet<i32>) {
+        for &index in &set {
+            self.containing
+                .entry(index)
+                .or_default()
+                .push(self.sets.len());
+        }
+        self.exact.insert(set.clone());
+        self.sets.push(set);
+    }
+
+    fn containing(&self, index: i32) -> &[usize] {
+        self.containing.get(&index).map_or(&[], Vec::as_slice)
+    }
+
+    fn dominate(&self, set: &BTreeSet<i32>, dominance: VisitDominance) -> bool {
+        match dominance {
+            VisitDominance::Exact => self.exact.contains(set),
+            // any superset is listed under the index of the set listed under the fewest others
+            VisitDominance::MoreVisits => {
+                match set.iter().min_by_key(|&&i| self.containing(i).len()) {
+                    Some(&rarest) => self
+                        .containing(rarest)
+                        .iter()
+                        .any(|&id| self.sets[id].is_superset(set)),
+                    None => !self.sets.is_empty(),
+                }
+            }
+            // few sets are left when keeping the smaller ones, so they are all gone through
+            VisitDominance::FewerVisits => self.sets.iter().any(|known| known.is_subset(set)),
+        }
+    }
+}
+
+/// Enumerates every code of at most `max_inputs` inputs from `from_node`, collapsing sequences as
+/// told by `dominance`. Codes never go through indices that crash, and codes ending on an index
+/// that crashes when stopped on are extended but left out of the catalog. Without pruning dominated
+/// sequences, the catalog grows about fivefold with every input.
+pub fn get_code_catalog(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    max_inputs: usize,
+    dominance: VisitDominance,
+) -> CodeCatalog {
+    let mut known: HashMap<i32, KnownSets> = HashMap::new();
+    known.entry(from_node).or_default().insert(BTreeSet::new());
+
+    let mut layer = vec![CatalogEntry {
+        end: from_node,
+        visited: BTreeSet::new(),
+        code: Vec::new(),
+    }];
+    let mut catalog = CodeCatalog::new();
+
+    for inputs in 0..=max_inputs {
+        let can_stop =
+            |entry: &&CatalogEntry| Hazard::of(entry.end) != Some(Hazard::CrashIfStopped);
+        catalog.push(layer.iter().filter(can_stop).cloned().collect());
+        if inputs == max_inputs {
+            break;
+        }
+
+        let mut candidates: Vec<_> = layer
+            .iter()
+            .flat_map(|entry| {
+                graph
+                    .edges(entry.end)
+                    .filter(|&(_, to, _)| Hazard::of(to) != Some(Hazard::Crash))
+                    .map(move |(_, to, &directions)| {
+                        let mut visited = entry.visited.clone();
+                        visited.insert(to);
+                        let mut code = entry.code.clone();
+                        code.push(directions);
+                        CatalogEntry {
+                            end: to,
+                            visited,
+                            code,
+                        }
+                    })
+            })
+            .collect();
+
+        // Dominating sets first, so that the ones they dominate are pruned in the same layer
+        candidates.sort_by(|a, b| {
+            let rank = |entry: &CatalogEntry| match dominance {
+                VisitDominance::MoreVisits => usize::MAX - entry.visited.len(),
+                _ => entry.visited.len(),
+            };
+            (rank(a), a.end, &a.visited).cmp(&(rank(b), b.end, &b.visited))
+        });
+
+        layer = candidates
+            .into_iter()
+            .filter(|candidate| {
+                let known_sets = known.entry(candidate.end).or_default();
+                if known_sets.dominate(&candidate.visited, dominance) {
+                    return false;
+                }
+                known_sets.insert(candidate.visited.clone());
+                true
+            })
+            .collect();
+        layer.sort_by(|a, b| (a.end, &a.visited).cmp(&(b.end, &b.visited)));
+    }
+
+    catalog
+}
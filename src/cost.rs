@@ -0,0 +1,393 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::Deserialize;
+
+use crate::hazard::EntryRisk;
+use crate::Directions;
+
+/// Decides how much each input costs, which is what the shortest code minimizes. Moves with an
+/// infinite cost are never made.
+pub trait CostModel: Sync {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32;
+
+    /// The rules making up the cost of the input, used to explain codes.
+    fn rules(&self, _from: i32, _to: i32, _directions: Directions) -> Vec<CostRule> {
+        Vec::new()
+    }
+
+    /// Extra cost of pressing `next` right after `previous`, both being single directions. Only
+    /// looked at when `prices_transitions` is true, since searching with transitions is slower.
+    fn transition_cost(&self, _previous: Directions, _next: Directions) -> f32 {
+        0.0
+    }
+
+    fn prices_transitions(&self) -> bool {
+        false
+    }
+
+    /// Cost of the cursor moving from `from` to `to` because `held` is still held down after the
+    /// last move, instead of being pressed again. The first repeat comes after a longer delay
+    /// than the next ones. Only looked at when `uses_holds` is true.
+    fn repeat_cost(&self, _from: i32, _to: i32, _held: Directions, _first: bool) -> f32 {
+        f32::INFINITY
+    }
+
+    /// Cost of a hold ending on `at`, since releasing it a little late moves the cursor once more,
+    /// onto `overshoot`. Only looked at when `uses_holds` is true.
+    fn overshoot_cost(&self, _at: i32, _overshoot: i32) -> f32 {
+        0.0
+    }
+
+    fn uses_holds(&self) -> bool {
+        false
+    }
+}
+
+impl<M: CostModel + ?Sized> CostModel for &M {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        (**self).cost(from, to, directions)
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        (**self).rules(from, to, directions)
+    }
+
+    fn transition_cost(&self, previous: Directions, next: Directions) -> f32 {
+        (**self).transition_cost(previous, next)
+    }
+
+    fn prices_transitions(&self) -> bool {
+        (**self).prices_transitions()
+    }
+
+    fn repeat_cost(&self, from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        (**self).repeat_cost(from, to, held, first)
+    }
+
+    fn overshoot_cost(&self, at: i32, overshoot: i32) -> f32 {
+        (**self).overshoot_cost(at, overshoot)
+    }
+
+    fn uses_holds(&self) -> bool {
+        (**self).uses_holds()
+    }
+}
+
+// Implements the listed methods pricing transitions and holds by forwarding them to the model in
+// `self.$base`, for models that wrap another one without changing those costs.
+macro_rules! forward_to_base {
+    ($base:ident: $($method:ident),+ $(,)?) => {
+        $(forward_to_base!(@$method $base);)+
+    };
+    (@transition_cost $base:ident) => {
+        fn transition_cost(&self, previous: $crate::Directions, next: $crate::Directions) -> f32 {
+            self.$base.transition_cost(previous, next)
+        }
+    };
+    (@prices_transitions $base:ident) => {
+        fn prices_transitions(&self) -> bool {
+            self.$base.prices_transitions()
+        }
+    };
+    (@repeat_cost $base:ident) => {
+        fn repeat_cost(&self, from: i32, to: i32, held: $crate::Directions, first: bool) -> f32 {
+            self.$base.repeat_cost(from, to, held, first)
+        }
+    };
+    (@overshoot_cost $base:ident) => {
+        fn overshoot_cost(&self, at: i32, overshoot: i32) -> f32 {
+            self.$base.overshoot_cost(at, overshoot)
+        }
+    };
+    (@uses_holds $base:ident) => {
+        fn uses_holds(&self) -> bool {
+            self.$base.uses_holds()
+        }
+    };
+}
+pub(crate) use forward_to_base;
+
+// The rule for the kind of input. Edges can be taken with any of their directions, so the easiest
+// one is used.
+pub(crate) fn input_rule(directions: Directions) -> CostRule {
+    if directions.has_straight() {
+        CostRule::Straight
+    } else if directions.has_diagonal() {
+        CostRule::Diagonal
+    } else {
+        CostRule::RealTimeImpossible
+    }
+}
+
+// The single direction pressed for an input that any of `directions` would make, preferring the
+// easiest kind of input.
+pub(crate) fn pressed(directions: Directions) -> Directions {
+    let kind = [
+        Directions::ANY_STRAIGHT,
+        Directions::ANY_DIAGONAL,
+        Directions::ANY_REAL_TIME_IMPOSSIBLE,
+    ]
+    .iter()
+    .map(|&kind| directions & kind)
+    .find(|candidates| !candidates.is_empty())
+    .unwrap_or(directions);
+    Directions::from_bits_truncate(1 << kind.bits().trailing_zeros())
+}
+
+// The straight directions held down to press a single direction.
+pub(crate) fn held_directions(pressed: Directions) -> Directions {
+    match pressed {
+        Directions::UP_LEFT => Directions::UP | Directions::LEFT,
+        Directions::UP_RIGHT => Directions::UP | Directions::RIGHT,
+        Directions::DOWN_LEFT => Directions::DOWN | Directions::LEFT,
+        Directions::DOWN_RIGHT => Directions::DOWN | Directions::RIGHT,
+        Directions::UP_DOWN => Directions::UP | Directions::DOWN,
+        Directions::LEFT_RIGHT => Directions::LEFT | Directions::RIGHT,
+        Directions::UP_LEFT_RIGHT => Directions::UP | Directions::LEFT | Directions::RIGHT,
+        Directions::UP_LEFT_DOWN => Directions::UP | Directions::LEFT | Directions::DOWN,
+        Directions::UP_RIGHT_DOWN => Directions::UP | Directions::RIGHT | Directions::DOWN,
+        Directions::LEFT_RIGHT_DOWN => Directions::LEFT | Directions::RIGHT | Directions::DOWN,
+        Directions::ALL_AT_ONCE => Directions::ANY_STRAIGHT,
+        _ => pressed,
+    }
+}
+
+pub(crate) fn model_edge_cost(
+    cost_model: &dyn CostModel,
+) -> impl Fn((i32, i32, &Directions)) -> f32 + Copy + Sync + '_ {
+    move |(from, to, w)| cost_model.cost(from, to, *w)
+}
+
+/// The rules making up the cost of an input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CostRule {
+    Straight,
+    Diagonal,
+    RealTimeImpossible,
+    TemporaryHazard,
+    Crash,
+    /// The index has its own penalty, replacing the one of its hazard.
+    IndexOverride,
+    /// Moving from the previous input to this one is awkward.
+    Transition,
+    /// The input is a diagonal that would do harm if it degraded to one of its directions.
+    RiskySlip,
+    /// The cursor moved again because the previous input is still held.
+    AutoRepeat,
+    /// Releasing the held input a little late would move the cursor somewhere harmful.
+    Overshoot,
+    /// Nobody knows what writing to the index does.
+    UntestedIndex,
+    /// Writing to the index was found to do harm, which no hazard documents.
+    KnownHazard,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionWeights {
+    pub straight: f32,
+    pub diagonal: f32,
+    /// Inputs that can't be made with a controller, such as opposite directions held together.
+    pub real_time_impossible: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HazardPenalties {
+    pub crash: f32,
+    pub temporary: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexOverride {
+    pub index: i32,
+    /// Added to the cost of every input entering the index, whatever its hazard.
+    pub penalty: f32,
+}
+
+/// A cost model made of a weight for each kind of input, plus a penalty for entering a hazard.
+/// Overrides are looked up from last to first, so that later ones win.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostProfile {
+    pub directions: DirectionWeights,
+    pub hazards: HazardPenalties,
+    pub overrides: Vec<IndexOverride>,
+}
+
+impl CostProfile {
+    /// Played in real time on a controller. Diagonals are risky to input, so they get a bigger
+    /// cost, and other directions are impossible, but still allowed at a high cost.
+    pub fn rta() -> CostProfile {
+        CostProfile {
+            directions: DirectionWeights {
+                straight: 1.0,
+                diagonal: 5.5,
+                real_time_impossible: 15.5,
+            },
+            hazards: HazardPenalties::default(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Every input is frame perfect, so only the number of inputs matters.
+    pub fn tas() -> CostProfile {
+        CostProfile {
+            directions: DirectionWeights {
+                straight: 1.0,
+                diagonal: 1.0,
+                real_time_impossible: 1.0,
+            },
+            hazards: HazardPenalties::default(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Played in real time on an emulator with a keyboard. Diagonals are only two keys held
+    /// together, and emulators don't filter out opposite directions.
+    pub fn emulator_keyboard() -> CostProfile {
+        CostProfile {
+            directions: DirectionWeights {
+                straight: 1.0,
+                diagonal: 2.0,
+                real_time_impossible: 3.0,
+            },
+            hazards: HazardPenalties::default(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Names accepted for `base` in profile files.
+    pub fn builtin(name: &str) -> Option<CostProfile> {
+        match name {
+            "rta" => Some(CostProfile::rta()),
+            "tas" => Some(CostProfile::tas()),
+            "emulator-keyboard" => Some(CostProfile::emulator_keyboard()),
+            _ => None,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<CostProfile> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // The rule for the index entered along with its penalty, if it has one.
+    fn index_rule(&self, to: i32) -> Option<(CostRule, f32)> {
+        if let Some(index_override) = self.overrides.iter().rev().find(|o| o.index == to) {
+            return Some((CostRule::IndexOverride, index_override.penalty));
+        }
+        self.hazards.rule(to)
+    }
+}
+
+impl Default for CostProfile {
+    fn default() -> CostProfile {
+        CostProfile::rta()
+    }
+}
+
+impl HazardPenalties {
+    // The rule for the hazard of the index entered along with its penalty, if it has one.
+    pub(crate) fn rule(&self, to: i32) -> Option<(CostRule, f32)> {
+        match EntryRisk::of(to) {
+            EntryRisk::Blocked => Some((CostRule::Crash, self.crash)),
+            EntryRisk::Temporary => Some((CostRule::TemporaryHazard, self.temporary)),
+            EntryRisk::Conditional | EntryRisk::Safe => None,
+        }
+    }
+}
+
+impl Default for HazardPenalties {
+    // Entering an index that only breaks things temporarily is allowed, but it should only be
+    // done when it saves that many straight inputs.
+    fn default() -> HazardPenalties {
+        HazardPenalties {
+            crash: f32::INFINITY,
+            temporary: 10.0,
+        }
+    }
+}
+
+impl CostModel for CostProfile {
+    fn cost(&self, _from: i32, to: i32, directions: Directions) -> f32 {
+        let input_cost = match input_rule(directions) {
+            CostRule::Straight => self.directions.straight,
+            CostRule::Diagonal => self.directions.diagonal,
+            _ => self.directions.real_time_impossible,
+        };
+        self.index_rule(to).map_or(0.0, |(_, penalty)| penalty) + input_cost
+    }
+
+    fn rules(&self, _from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        std::iter::once(input_rule(directions))
+            .chain(self.index_rule(to).map(|(rule, _)| rule))
+            .collect()
+    }
+}
+
+// A profile file starts from a built-in profile, RTA unless `base` says otherwise, and replaces
+// whatever it sets:
+//
+//     base = "tas"
+//     [directions]
+//     diagonal = 2.0
+//     [hazards]
+//     temporary = inf
+//     [[overrides]]
+//     index = -1347
+//     penalty = 3.0
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    base: Option<String>,
+    #[serde(default)]
+    directions: DirectionWeightsFile,
+    #[serde(default)]
+    hazards: HazardPenaltiesFile,
+    #[serde(default)]
+    overrides: Vec<IndexOverride>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DirectionWeightsFile {
+    straight: Option<f32>,
+    diagonal: Option<f32>,
+    real_time_impossible: Option<f32>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HazardPenaltiesFile {
+    crash: Option<f32>,
+    temporary: Option<f32>,
+}
+
+impl FromStr for CostProfile {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<CostProfile, toml::de::Error> {
+        let file: ProfileFile = toml::from_str(s)?;
+        let base = file.base.as_deref().unwrap_or("rta");
+        let mut profile = CostProfile::builtin(base)
+            .ok_or_else(|| toml::de::Error::custom(format!("unknown base profile `{}`", base)))?;
+
+        let directions = &mut profile.directions;
+        directions.straight = file.directions.straight.unwrap_or(directions.straight);
+        directions.diagonal = file.directions.diagonal.unwrap_or(directions.diagonal);
+        directions.real_time_impossible = file
+            .directions
+            .real_time_impossible
+            .unwrap_or(directions.real_time_impossible);
+        let hazards = &mut profile.hazards;
+        hazards.crash = file.hazards.crash.unwrap_or(hazards.crash);
+        hazards.temporary = file.hazards.temporary.unwrap_or(hazards.temporary);
+        profile.overrides.extend(file.overrides);
+
+        Ok(profile)
+    }
+}
@@ -0,0 +1,225 @@
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graphmap;
+
+use crate::cost::{forward_to_base, CostModel, CostRule};
+use crate::{get_shortest_code, Directions, EndCondition, Goal, ShortestCode};
+
+/// The input device the code is entered with, which decides the combos that can be pressed at all.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Device {
+    /// The original controller: its rocker can't press opposite directions together.
+    DPad,
+    /// An analog stick read as a d-pad, which only ever points one way, so not even diagonally.
+    AnalogAsDPad,
+    /// A keyboard on an emulator, which doesn't filter out opposite directions. Most keyboards
+    /// can't register all four arrow keys at once.
+    Keyboard,
+    Tas,
+}
+
+impl Device {
+    /// The combos the device can press out of the box.
+    pub fn default_combos(&self) -> Directions {
+        match self {
+            Device::DPad => Directions::ANY_STRAIGHT | Directions::ANY_DIAGONAL,
+            Device::AnalogAsDPad => Directions::ANY_STRAIGHT,
+            Device::Keyboard => Directions::all() - Directions::ALL_AT_ONCE,
+            Device::Tas => Directions::all(),
+        }
+    }
+}
+
+/// A device along with the combos it can press, which can be changed for a given setup, such as a
+/// keyboard registering all four arrow keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceProfile {
+    pub device: Device,
+    pub combos: Directions,
+}
+
+impl DeviceProfile {
+    /// Whether any of `directions` can be pressed, since they all make the same input.
+    pub fn can_press(&self, directions: Directions) -> bool {
+        directions.intersects(self.combos)
+    }
+
+    pub fn check_code(&self, code: &[Directions]) -> Result<(), UnsupportedInput> {
+        match code.iter().position(|&input| !self.can_press(input)) {
+            Some(step) => Err(UnsupportedInput {
+                step,
+                input: code[step],
+                device: self.device,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<Device> for DeviceProfile {
+    fn from(device: Device) -> DeviceProfile {
+        DeviceProfile {
+            device,
+            combos: device.default_combos(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedInput {
+    /// Position of the input in the code.
+    pub step: usize,
+    pub input: Directions,
+    pub device: Device,
+}
+
+impl fmt::Display for UnsupportedInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "input {} ({}) can't be made on {:?}",
+            self.step + 1,
+            self.input,
+            self.device
+        )
+    }
+}
+
+impl Error for UnsupportedInput {}
+
+/// Restricts a cost model to the combos of a device profile. Inputs left without any direction
+/// can't be made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceCostModel<M> {
+    pub base: M,
+    pub profile: DeviceProfile,
+}
+
+impl<M: CostModel> CostModel for DeviceCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        let available = directions & self.profile.combos;
+        if available.is_empty() {
+            f32::INFINITY
+        } else {
+            self.base.cost(from, to, available)
+        }
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        self.base.rules(from, to, directions & self.profile.combos)
+    }
+
+    fn repeat_cost(&self, from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        if self.profile.can_press(held) {
+            self.base.repeat_cost(from, to, held, first)
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    forward_to_base!(base: transition_cost, prices_transitions, overshoot_cost, uses_holds);
+}
+
+// Added to inputs a device can't make when looking for the one a route needs, so that they are
+// only used when nothing else reaches the goals.
+const UNSUPPORTED_INPUT_PENALTY: f32 = 1000.0;
+
+// Lets a route use inputs a device can't make even when the base model forbids them, such as a
+// profile making real time impossible inputs infinitely expensive. They are priced as a straight
+// input entering the same index, plus a penalty.
+struct UnsupportedInputCostModel<M> {
+    base: M,
+    profile: DeviceProfile,
+}
+
+impl<M: CostModel> CostModel for UnsupportedInputCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        let cost = self.base.cost(from, to, directions);
+        if cost.is_finite() || directions & self.profile.combos == directions {
+            cost
+        } else {
+            self.base.cost(from, to, Directions::UP) + UNSUPPORTED_INPUT_PENALTY
+        }
+    }
+
+    forward_to_base!(
+        base: transition_cost,
+        prices_transitions,
+        repeat_cost,
+        overshoot_cost,
+        uses_holds
+    );
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceRouteError {
+    /// No code reaches the goals, whatever the device.
+    Unreachable,
+    /// The goals can only be reached with an input the device can't make, such as the one in the
+    /// cheapest code for any device.
+    Unsupported(UnsupportedInput),
+}
+
+impl fmt::Display for DeviceRouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviceRouteError::Unreachable => write!(f, "no code reaches every goal"),
+            DeviceRouteError::Unsupported(unsupported) => {
+                write!(
+                    f,
+                    "every code needs an input the device can't make: {}",
+                    unsupported
+                )
+            }
+        }
+    }
+}
+
+impl Error for DeviceRouteError {}
+
+/// Same as `get_shortest_code`, but only with inputs the device can make. When the goals can't be
+/// reached that way, tells whether it is because of the device.
+pub fn get_shortest_code_for_device(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+    device: impl Into<DeviceProfile>,
+) -> Result<ShortestCode, DeviceRouteError> {
+    let profile = device.into();
+    let device_cost_model = DeviceCostModel {
+        base: cost_model,
+        profile,
+    };
+    if let Some(mut shortest_code) =
+        get_shortest_code(graph, from_node, goals, end, &device_cost_model)
+    {
+        // only show the directions that can be pressed
+        for (_, code) in &mut shortest_code.code_segments {
+            code.iter_mut().for_each(|input| *input &= profile.combos);
+        }
+        for step in &mut shortest_code.steps {
+            step.input &= profile.combos;
+        }
+        return Ok(shortest_code);
+    }
+
+    let unsupported_cost_model = UnsupportedInputCostModel {
+        base: cost_model,
+        profile,
+    };
+    let shortest_code = get_shortest_code(graph, from_node, goals, end, &unsupported_cost_model)
+        .ok_or(DeviceRouteError::Unreachable)?;
+    let code: Vec<_> = shortest_code
+        .code_segments
+        .iter()
+        .flat_map(|(_, code)| code.iter().cloned())
+        .collect();
+    match profile.check_code(&code) {
+        Err(unsupported) => Err(DeviceRouteError::Unsupported(unsupported)),
+        // a code the device can make would have been found above
+        Ok(()) => Err(DeviceRouteError::Unreachable),
+    }
+}
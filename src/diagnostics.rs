@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use petgraph::graphmap;
+
+use crate::algo;
+use crate::route::compare_inputs;
+use crate::{Directions, Goal, Hazard};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Unreachability {
+    /// Not found within the generated graph, but some indices at its edge were never expanded: a
+    /// deeper graph may reach it.
+    OutsideDepth,
+    /// Reachable only by entering indices that crash. These are the crashing indices along the path
+    /// crossing the fewest of them.
+    BlockedByHazards(Vec<i32>),
+    /// Not reachable with the moves known. Either the graph holds the goal, so it lies within the
+    /// generated depth, but nothing reached leads to it, or nothing reached is left unexpanded.
+    Unreachable,
+}
+
+/// An index that can be reached safely, with the crashing indices the path from it to the goal
+/// crosses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReachableAlternative {
+    pub index: i32,
+    pub crossed_hazards: Vec<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnreachableGoal {
+    pub index: i32,
+    pub reason: Unreachability,
+    /// The safely reachable index the fewest inputs away from the goal, which may be close enough
+    /// to be used instead. Goals missing from the graph have none.
+    pub nearest_reachable: Option<ReachableAlternative>,
+}
+
+/// Explains why each goal that can't be reached from `from_node` isn't. Goals that can be reached
+/// on their own are left out, even when no single code reaches all of them.
+pub fn diagnose_unreachable_goals(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+) -> Vec<UnreachableGoal> {
+    let is_crash = |index| Hazard::of(index) == Some(Hazard::Crash);
+    let safely_reachable = algo::reachable(graph, from_node, None, |index| !is_crash(index));
+    let reachable = algo::reachable(graph, from_node, None, |_| true);
+    // fully expanded indices always move somewhere, even if only to themselves
+    let reaches_unexpanded = reachable
+        .iter()
+        .any(|&index| graph.neighbors(index).next().is_none());
+
+    goals
+        .iter()
+        .map(Goal::index)
+        .filter(|index| !safely_reachable.contains(index))
+        .map(|index| {
+            // a goal the graph holds was found within its depth, and all the moves leading to it
+            // from the indices reached are known
+            let reason = if reachable.contains(&index) {
+                Unreachability::BlockedByHazards(
+                    crossed_hazards(graph, from_node, |node| node == index).1,
+                )
+            } else if reaches_unexpanded && !graph.contains_node(index) {
+                Unreachability::OutsideDepth
+            } else {
+                Unreachability::Unreachable
+            };
+            let nearest_reachable = nearest_reachable(graph, &safely_reachable, index);
+
+            UnreachableGoal {
+                index,
+                reason,
+                nearest_reachable,
+            }
+        })
+        .collect()
+}
+
+// Searches backwards from the goal for the nearest index that can be reached safely and stopped
+// on, and lists the crashes on the way from it to the goal.
+fn nearest_reachable(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    safely_reachable: &HashSet<i32>,
+    goal: i32,
+) -> Option<ReachableAlternative> {
+    if !graph.contains_node(goal) {
+        return None;
+    }
+    let reversed_graph: graphmap::DiGraphMap<i32, Directions> = graph
+        .all_edges()
+        .map(|(from, to, &directions)| (to, from, directions))
+        .collect();
+    let is_alternative = |index| {
+        safely_reachable.contains(&index) && Hazard::of(index) != Some(Hazard::CrashIfStopped)
+    };
+    let (path, crossed_hazards) = crossed_hazards(&reversed_graph, goal, is_alternative);
+    let &index = path.last()?;
+    Some(ReachableAlternative {
+        index,
+        crossed_hazards,
+    })
+}
+
+// The path to the nearest node accepted by `is_goal` crossing the fewest crashes, along with the
+// crashes it crosses, start included.
+fn crossed_hazards<P>(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    is_goal: P,
+) -> (Vec<i32>, Vec<i32>)
+where
+    P: Fn(i32) -> bool,
+{
+    let is_crash = |index| Hazard::of(index) == Some(Hazard::Crash);
+    // Crossing a crash costs more than any path without one, so that as few are crossed as
+    // possible.
+    let crash_cost = graph.node_count() as f32;
+    let edge_cost = |(from, to, _): (i32, i32, &Directions)| {
+        if is_crash(to) || is_crash(from) {
+            crash_cost
+        } else {
+            1.0
+        }
+    };
+    let tie_break = |a: &[i32], b: &[i32]| compare_inputs(graph, a, b);
+
+    algo::dijkstra_nearest(graph, from_node, is_goal, edge_cost, tie_break)
+        .map(|(_, path)| {
+            let crashes = path
+                .iter()
+                .cloned()
+                .filter(|&index| is_crash(index))
+                .collect();
+            (path, crashes)
+        })
+        .unwrap_or_default()
+}
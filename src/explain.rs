@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use petgraph::graphmap;
+
+use crate::algo;
+use crate::cost::{input_rule, model_edge_cost, pressed, CostModel, CostRule};
+use crate::hold::hold_end_cost;
+use crate::oracle::DistanceOracle;
+use crate::route::{compare_inputs, split_code};
+use crate::{Directions, Goal};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alternative {
+    pub input: Directions,
+    pub to: i32,
+    pub cost: f32,
+    pub rules: Vec<CostRule>,
+    /// Cost of the cheapest way to the same next goal after taking this input instead.
+    pub cost_to_next_goal: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub input: Directions,
+    pub from: i32,
+    pub to: i32,
+    pub cost: f32,
+    pub rules: Vec<CostRule>,
+    pub total: f32,
+    /// The goal met for the first time with this input, if any.
+    pub goal: Option<i32>,
+    pub cost_to_next_goal: f32,
+    /// The other input at this step leading to the next goal most cheaply, which is never cheaper
+    /// than the one taken.
+    pub cheapest_alternative: Option<Alternative>,
+    /// Whether the cursor moved by the previous input being held rather than by pressing this one.
+    pub repeat: bool,
+}
+
+// Explains every input along the cursor path. The next goal of a step is where the code segment
+// it belongs to ends, which is the end of the code for the closing segment. Costs to the next goal
+// are looked up in the oracle when there is one, and leave out transitions between inputs and
+// holds. `repeats` tells which moves are made by holding the previous input.
+pub(crate) fn explain_code(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    goals: &[Goal],
+    cursor_path: &[i32],
+    repeats: &[bool],
+    cost_model: &dyn CostModel,
+    oracle: Option<&DistanceOracle>,
+) -> Vec<Step> {
+    // Without an oracle, costs to the next goal are found by searching backwards from it.
+    let reversed_graph: graphmap::DiGraphMap<i32, Directions> = match oracle {
+        Some(_) => graphmap::DiGraphMap::new(),
+        None => graph
+            .all_edges()
+            .map(|(from, to, &directions)| (to, from, directions))
+            .collect(),
+    };
+    let edge_cost = model_edge_cost(cost_model);
+    // The cost of an input after the previous one, along with the rules making it up.
+    let priced = |e @ (from, to, w): (i32, i32, &Directions), previous: Option<Directions>| {
+        let mut rules = cost_model.rules(from, to, *w);
+        let transition_cost = match previous {
+            Some(previous) if cost_model.prices_transitions() => {
+                cost_model.transition_cost(previous, pressed(*w))
+            }
+            _ => 0.0,
+        };
+        if transition_cost != 0.0 {
+            rules.push(CostRule::Transition);
+        }
+        (edge_cost(e) + transition_cost, rules)
+    };
+    let reversed_edge_cost = |(from, to, w): (i32, i32, &Directions)| edge_cost((to, from, w));
+    let tie_break = |a: &[i32], b: &[i32]| compare_inputs(&reversed_graph, a, b);
+
+    let mut steps = Vec::new();
+    let mut total = 0.0;
+    let mut met_goals = vec![cursor_path[0]];
+    let mut previous = None;
+    let mut repeating = false;
+    let mut segment_start = 0;
+    for (next_goal, segment) in split_code(graph, goals, cursor_path) {
+        let segment_path = &cursor_path[segment_start..=segment_start + segment.len()];
+        segment_start += segment.len();
+
+        let next_nodes = segment_path
+            .iter()
+            .flat_map(|&from| graph.neighbors(from))
+            .collect();
+        let costs_to_next_goal = match oracle {
+            Some(_) => HashMap::new(),
+            None => algo::dijkstra(
+                &reversed_graph,
+                next_goal,
+                next_nodes,
+                reversed_edge_cost,
+                tie_break,
+            ),
+        };
+        let cost_to_next_goal = |to| {
+            match oracle {
+                Some(oracle) => oracle.cost(to, next_goal),
+                None => costs_to_next_goal[&to].as_ref().map(|(cost, _)| *cost),
+            }
+            .unwrap_or(f32::INFINITY)
+        };
+
+        for (i, (&from, &to)) in segment_path
+            .iter()
+            .zip(segment_path.iter().skip(1))
+            .enumerate()
+        {
+            let e = (from, to, graph.edge_weight(from, to).unwrap());
+            let repeat = repeats[segment_start - segment.len() + i];
+            let (cost, rules) = match previous {
+                Some(held) if repeat => {
+                    let rules = std::iter::once(CostRule::AutoRepeat)
+                        .chain(
+                            cost_model
+                                .rules(from, to, *e.2)
+                                .into_iter()
+                                .filter(|&rule| rule != input_rule(*e.2)),
+                        )
+                        .collect();
+                    (cost_model.repeat_cost(from, to, held, !repeating), rules)
+                }
+                Some(held) if repeating => {
+                    let (cost, mut rules) = priced(e, previous);
+                    let hold_end_cost = hold_end_cost(graph, from, held, cost_model);
+                    if hold_end_cost != 0.0 {
+                        rules.push(CostRule::Overshoot);
+                    }
+                    (cost + hold_end_cost, rules)
+                }
+                _ => priced(e, previous),
+            };
+            total += cost;
+
+            let cheapest_alternative = graph
+                .edges(from)
+                .filter(|&(_, alternative_to, _)| alternative_to != to)
+                .map(|e| {
+                    let (cost, rules) = priced(e, previous);
+                    Alternative {
+                        input: *e.2,
+                        to: e.1,
+                        cost,
+                        rules,
+                        cost_to_next_goal: cost_to_next_goal(e.1),
+                    }
+                })
+                .filter(|alternative| {
+                    (alternative.cost + alternative.cost_to_next_goal).is_finite()
+                })
+                .min_by(|a, b| {
+                    (a.cost + a.cost_to_next_goal).total_cmp(&(b.cost + b.cost_to_next_goal))
+                });
+
+            let goal = Some(to).filter(|to| {
+                goals.iter().any(|goal| goal.index() == *to) && !met_goals.contains(to)
+            });
+            met_goals.extend(goal);
+
+            steps.push(Step {
+                input: *e.2,
+                from,
+                to,
+                cost,
+                rules,
+                total,
+                goal,
+                cost_to_next_goal: cost_to_next_goal(to),
+                cheapest_alternative,
+                repeat,
+            });
+            if !repeat {
+                previous = Some(pressed(*e.2));
+            }
+            repeating = repeat;
+        }
+    }
+
+    steps
+}
@@ -0,0 +1,78 @@
+use crate::cost::held_directions;
+use crate::Directions;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Hazard {
+    /// Crashes, softlocks or freezes the name selection screen as soon as the cursor enters the
+    /// index, so the code cannot be finished.
+    Crash,
+    /// Only crashes if the cursor stops on the index. Passing through it is fine.
+    CrashIfStopped,
+    /// Only crashes on exiting the name selection screen with the cursor on the index, so the code
+    /// can still end there when the screen is left some other way. See `EndCondition::safe_exit`.
+    CrashOnExit,
+    /// Breaks something until the screen or the hub is reloaded, without preventing the code from
+    /// being finished.
+    Temporary,
+}
+
+impl Hazard {
+    /// Known hazards, as documented in the README.
+    pub fn of(index: i32) -> Option<Hazard> {
+        match index {
+            -896 | -945 | -979 | -1014 | -1018 | -1025 | -1039 | -1207 | -1313 | -1314 | -1317
+            | -1348 | -1353 | -1381 | -1383 | -1388 | -1397 | -1400 | -1409 | -1424 | -1664
+            | -1954 => Some(Hazard::Crash),
+
+            // These recover upon exiting the screen, but the frozen screen won't take the rest of
+            // the code.
+            -2205 | -2255 | -2271 | -2275 | -2277 | -2280 | -2282 => Some(Hazard::Crash),
+
+            -1347 => Some(Hazard::CrashIfStopped),
+
+            -1064 | -1373 | -1375 | -1420 => Some(Hazard::CrashOnExit),
+
+            -63 | -72 => Some(Hazard::Temporary),
+
+            _ => None,
+        }
+    }
+}
+
+// What entering an index means for a code going through it. Costs sort hazards through this, so
+// that they agree on which ones block a code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum EntryRisk {
+    // The code can't go on past the index.
+    Blocked,
+    // The code goes on, but something stays broken for a while.
+    Temporary,
+    // The code goes on, and only crashes depending on where it stops or how the screen is left,
+    // which is up to the end of the code.
+    Conditional,
+    Safe,
+}
+
+impl EntryRisk {
+    pub(crate) fn of(index: i32) -> EntryRisk {
+        match Hazard::of(index) {
+            Some(Hazard::Crash) => EntryRisk::Blocked,
+            Some(Hazard::Temporary) => EntryRisk::Temporary,
+            Some(Hazard::CrashIfStopped) | Some(Hazard::CrashOnExit) => EntryRisk::Conditional,
+            None => EntryRisk::Safe,
+        }
+    }
+}
+
+// Inputs that stop moving the cursor as expected once the index has been written to, for the rest
+// of the time spent on the screen.
+pub(crate) fn broken_inputs(index: i32) -> Directions {
+    let broken_direction = match index {
+        -72 => Directions::LEFT,
+        _ => return Directions::empty(),
+    };
+    (0..15)
+        .map(|bit| Directions::from_bits_truncate(1 << bit))
+        .filter(|&input| held_directions(input).contains(broken_direction))
+        .fold(Directions::empty(), |broken, input| broken | input)
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use petgraph::graphmap;
+
+use crate::cost::{pressed, CostModel};
+use crate::misinput::move_cursor;
+use crate::Directions;
+
+/// What the player does on the name selection screen: either press an input once, or hold it down
+/// for the cursor to move `repeats` more times on its own. A held input is a single direction,
+/// since the others making the same first move may not repeat the same way.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Press(Directions),
+    Hold { input: Directions, repeats: usize },
+}
+
+// The cost of a hold of `held` ending on `at`, which moves the cursor on if released late.
+pub(crate) fn hold_end_cost(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    at: i32,
+    held: Directions,
+    cost_model: &dyn CostModel,
+) -> f32 {
+    move_cursor(graph, at, held).map_or(0.0, |overshoot| cost_model.overshoot_cost(at, overshoot))
+}
+
+// The previous input, and whether the cursor moved last by repeating it.
+type HoldState = (Directions, bool);
+
+// Tells which moves along a path found with holds are repeats of a held input, picking the
+// cheapest way to input the path with `cost_model`. The path is input as if nothing was held
+// before it, and presses are preferred over holds that cost the same. Like in searches, the path
+// only ends with a hold when releasing it late costs nothing.
+pub(crate) fn label_repeats(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    path: &[i32],
+    cost_model: &dyn CostModel,
+) -> Vec<bool> {
+    // the cheapest way to reach each state after every move, and the state it came from
+    let mut layers: Vec<BTreeMap<HoldState, (f32, HoldState, bool)>> = Vec::new();
+    let mut reached: BTreeMap<HoldState, f32> =
+        std::iter::once(((Directions::empty(), false), 0.0)).collect();
+
+    for (&from, &to) in path.iter().zip(path.iter().skip(1)) {
+        let directions = *graph.edge_weight(from, to).unwrap();
+        let mut layer = BTreeMap::new();
+        let mut relax = |state, cost: f32, previous, repeat| {
+            let best = layer
+                .entry(state)
+                .or_insert((f32::INFINITY, previous, repeat));
+            if cost < best.0 {
+                *best = (cost, previous, repeat);
+            }
+        };
+
+        for (&previous @ (held, repeating), &cost) in &reached {
+            let transition_cost = if held.is_empty() || !cost_model.prices_transitions() {
+                0.0
+            } else {
+                cost_model.transition_cost(held, pressed(directions))
+            };
+            let hold_end_cost = if repeating {
+                hold_end_cost(graph, from, held, cost_model)
+            } else {
+                0.0
+            };
+            let press_cost =
+                cost_model.cost(from, to, directions) + transition_cost + hold_end_cost;
+            relax(
+                (pressed(directions), false),
+                cost + press_cost,
+                previous,
+                false,
+            );
+
+            if !held.is_empty() && move_cursor(graph, from, held) == Some(to) {
+                let repeat_cost = cost_model.repeat_cost(from, to, held, !repeating);
+                relax((held, true), cost + repeat_cost, previous, true);
+            }
+        }
+
+        reached = layer
+            .iter()
+            .map(|(&state, &(cost, _, _))| (state, cost))
+            .collect();
+        layers.push(layer);
+    }
+
+    let mut state = match reached
+        .iter()
+        .filter(|&(&(held, repeating), _)| {
+            !repeating || hold_end_cost(graph, path[path.len() - 1], held, cost_model) == 0.0
+        })
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(&state, _)| state)
+    {
+        Some(state) => state,
+        None => return Vec::new(),
+    };
+    let mut repeats: Vec<_> = layers
+        .iter()
+        .rev()
+        .map(|layer| {
+            let (_, previous, repeat) = layer[&state];
+            state = previous;
+            repeat
+        })
+        .collect();
+    repeats.reverse();
+    repeats
+}
@@ -1,14 +1,88 @@
 #[macro_use] extern crate bitflags;
 
-use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::io;
 use std::iter::Iterator;
 
 use petgraph::graphmap;
-use petgraph::visit::EdgeRef;
 
-mod algo;
+pub mod algo;
+
+mod anytime;
+pub use anytime::{get_shortest_code_anytime, AnytimeCode, CancellationToken, Progress};
+
+mod batch;
+pub use batch::{get_shortest_codes, RouteQuery};
+
+mod budget;
+pub use budget::{get_budgeted_codes, Budget, BudgetedCode};
+
+mod catalog;
+pub use catalog::{get_code_catalog, CatalogEntry, CodeCatalog, VisitDominance};
+
+mod cost;
+pub use cost::{
+    CostModel, CostProfile, CostRule, DirectionWeights, HazardPenalties, IndexOverride,
+};
+
+mod device;
+pub use device::{
+    get_shortest_code_for_device, Device, DeviceCostModel, DeviceProfile, DeviceRouteError,
+    UnsupportedInput,
+};
+
+mod diagnostics;
+pub use diagnostics::{
+    diagnose_unreachable_goals, ReachableAlternative, Unreachability, UnreachableGoal,
+};
+
+mod explain;
+pub use explain::{Alternative, Step};
+
+mod hazard;
+pub use hazard::Hazard;
+
+mod hold;
+pub use hold::Action;
+
+mod misinput;
+pub use misinput::{simulate_misinputs, InputReliability, Misinput, SuccessCostModel};
+
+mod oracle;
+pub use oracle::{DistanceOracle, OracleError};
+
+mod pareto;
+pub use pareto::{get_pareto_codes, ParetoCode, RouteCosts};
+
+mod protect;
+pub use protect::{written_words, ProtectedMemory, ProtectedMemoryCostModel};
+
+mod reach;
+pub use reach::{get_reaching_indices, ReverseIndex};
+
+mod recovery;
+pub use recovery::{plan_recovery, Recovery, RecoveryError};
+
+mod risk;
+pub use risk::{IndexKnowledge, RiskCostModel};
+
+mod route;
+pub use route::{
+    get_shortest_code, get_shortest_code_with_oracle, get_shortest_code_with_tie_breaks,
+    CodeSegments, EndCondition, Goal, ShortestCode, EXIT_CRASH_INDICES,
+};
+
+mod slip;
+pub use slip::{analyze_slips, Slip, SlipCostModel, SlipPenalties};
+
+mod tie_break;
+pub use tie_break::{TieBreak, DEFAULT_TIE_BREAKS};
+
+mod timing;
+pub use timing::{AutoRepeat, FrameCostModel, FrameTiming, Timing};
+
+mod transition;
+pub use transition::{TransitionCostModel, TransitionTable};
 
 mod utils;
 use utils::ItWithFallback;
@@ -103,6 +177,48 @@ impl std::fmt::Display for Directions {
 const RAM_MOVE_BEHAVIOUR_ROOT_ADDRESS: i64 = 0x0BD5DA;
 const RAM_MOVE_BEHAVIOUR_INDEX_OFFSET: i64 = 0x10; // 16 bytes
 
+// The directional behavior table in RAM is shaped as sets of 4 bytes, separated from each
+// adjacent sets by 12 bytes. The 4 bytes represent, respectively, the left, right, up then down
+// behaviors.
+//
+// When composing multiple directions, the game simply adds the current index's horizontal and
+// vertical behaviors for the current panel index. This is what causes the menu OoB.
+pub(crate) fn read_moves(
+    ram_dump: &mut (impl io::Read + io::Seek),
+    panel_idx: i32,
+) -> io::Result<[(Directions, i32); 15]> {
+    ram_dump.seek(io::SeekFrom::Start(
+        (RAM_MOVE_BEHAVIOUR_ROOT_ADDRESS + panel_idx as i64 * RAM_MOVE_BEHAVIOUR_INDEX_OFFSET)
+            as u64,
+    ))?;
+
+    let mut bytes = [0u8; 4];
+    ram_dump.read_exact(&mut bytes)?;
+
+    let l = bytes[0] as i8 as i32;
+    let r = bytes[1] as i8 as i32;
+    let u = bytes[2] as i8 as i32;
+    let d = bytes[3] as i8 as i32;
+
+    Ok([
+        (Directions::UP, panel_idx + u),
+        (Directions::LEFT, panel_idx + l),
+        (Directions::RIGHT, panel_idx + r),
+        (Directions::DOWN, panel_idx + d),
+        (Directions::UP_LEFT, panel_idx + u + l),
+        (Directions::UP_RIGHT, panel_idx + u + r),
+        (Directions::DOWN_LEFT, panel_idx + d + l),
+        (Directions::DOWN_RIGHT, panel_idx + d + r),
+        (Directions::UP_DOWN, panel_idx + u + d),
+        (Directions::LEFT_RIGHT, panel_idx + l + r),
+        (Directions::UP_LEFT_RIGHT, panel_idx + u + l + r),
+        (Directions::UP_LEFT_DOWN, panel_idx + u + l + d),
+        (Directions::UP_RIGHT_DOWN, panel_idx + u + r + d),
+        (Directions::LEFT_RIGHT_DOWN, panel_idx + l + r + d),
+        (Directions::ALL_AT_ONCE, panel_idx + u + l + r + d),
+    ])
+}
+
 pub fn generate(
     ram_dump: &mut (impl io::Read + io::Seek),
     max_depth: i32,
@@ -121,27 +237,7 @@ pub fn generate(
         depth: max_depth,
     });
 
-    // The directional behavior table in RAM is shaped as sets of 4 bytes, separated from each
-    // adjacent sets by 12 bytes. The 4 bytes represent, respectively, the left, right, up then down
-    // behaviors.
-    //
-    // When composing multiple directions, the game simply adds the current index's horizontal and
-    // vertical behaviors for the current panel index. This is what causes the menu OoB.
-
     while let Some(cur_node) = queue.pop_front() {
-        ram_dump.seek(io::SeekFrom::Start(
-            (RAM_MOVE_BEHAVIOUR_ROOT_ADDRESS
-                + cur_node.panel_idx as i64 * RAM_MOVE_BEHAVIOUR_INDEX_OFFSET) as u64,
-        ))?;
-
-        let mut bytes = [0u8; 4];
-        ram_dump.read_exact(&mut bytes)?;
-
-        let l = bytes[0] as i8 as i32;
-        let r = bytes[1] as i8 as i32;
-        let u = bytes[2] as i8 as i32;
-        let d = bytes[3] as i8 as i32;
-
         let mut add_node_edge = |directions: Directions, to_panel_idx: i32| {
             if !graph.contains_node(to_panel_idx) {
                 graph.add_node(to_panel_idx);
@@ -156,27 +252,13 @@ pub fn generate(
             if let Some(edge_direction) = graph.edge_weight_mut(cur_node.panel_idx, to_panel_idx) {
                 *edge_direction |= directions;
             } else {
-                graph.add_edge(cur_node.panel_idx, to_panel_idx, directions.into());
+                graph.add_edge(cur_node.panel_idx, to_panel_idx, directions);
             }
         };
 
-        add_node_edge(Directions::UP, cur_node.panel_idx + u);
-        add_node_edge(Directions::LEFT, cur_node.panel_idx + l);
-        add_node_edge(Directions::RIGHT, cur_node.panel_idx + r);
-        add_node_edge(Directions::DOWN, cur_node.panel_idx + d);
-
-        add_node_edge(Directions::UP_LEFT, cur_node.panel_idx + u + l);
-        add_node_edge(Directions::UP_RIGHT, cur_node.panel_idx + u + r);
-        add_node_edge(Directions::DOWN_LEFT, cur_node.panel_idx + d + l);
-        add_node_edge(Directions::DOWN_RIGHT, cur_node.panel_idx + d + r);
-
-        add_node_edge(Directions::UP_DOWN, cur_node.panel_idx + u + d);
-        add_node_edge(Directions::LEFT_RIGHT, cur_node.panel_idx + l + r);
-        add_node_edge(Directions::UP_LEFT_RIGHT, cur_node.panel_idx + u + l + r);
-        add_node_edge(Directions::UP_LEFT_DOWN, cur_node.panel_idx + u + l + d);
-        add_node_edge(Directions::UP_RIGHT_DOWN, cur_node.panel_idx + u + r + d);
-        add_node_edge(Directions::LEFT_RIGHT_DOWN, cur_node.panel_idx + l + r + d);
-        add_node_edge(Directions::ALL_AT_ONCE, cur_node.panel_idx + u + l + r + d);
+        for &(directions, to_panel_idx) in read_moves(ram_dump, cur_node.panel_idx)?.iter() {
+            add_node_edge(directions, to_panel_idx);
+        }
 
         // Debug
         #[cfg(debug_assertions)]
@@ -198,118 +280,3 @@ pub fn generate(
 
     Ok(graph)
 }
-
-fn edge_cost((_, to, w): (i32, i32, &Directions)) -> f32 {
-    if to == -896
-        || to == -945
-        || to == -979
-        || to == -1014
-        || to == -1018
-        || to == -1025
-        || to == -1039
-        || to == -1064
-        || to == -1207
-        || to == -1313
-        || to == -1314
-        || to == -1317
-        || to == -1347
-        || to == -1353
-        || to == -1373
-        || to == -1375
-        || to == -1381
-        || to == -1383
-        || to == -1388
-        || to == -1397
-        || to == -1400
-        || to == -1409
-        || to == -1420
-        || to == -1424
-        || to == -1664
-        || to == -1954
-        || to == -2205
-        || to == -2271
-        || to == -2275
-        || to == -2277
-        || to == -2280
-        || to == -2282
-    {
-        return f32::INFINITY;
-    }
-
-    // Because diagonals are risky to input RTA in game, we'll give them a bigger cost.
-    // Also, because other directioans are impossible RTA, they have infinite weight.
-    // This should probably be configurable.
-    if w.has_straight() {
-        1.0
-    } else if w.has_diagonal() {
-        5.5
-    } else {
-        15.5
-    }
-}
-
-pub fn get_shortest_code(
-    graph: &graphmap::DiGraphMap<i32, Directions>,
-    from_node: i32,
-    goal_nodes: &[i32],
-) -> Option<(Vec<(i32, Vec<Directions>)>, f32)> {
-    let shortest_path_descriptions: HashMap<_, _> =
-        std::iter::once((from_node, goal_nodes.iter().cloned().collect::<Vec<_>>()))
-            .chain(goal_nodes.iter().map(|&from| {
-                let to = goal_nodes
-                    .iter()
-                    .filter(|&to| *to != from)
-                    .cloned()
-                    .collect::<Vec<_>>();
-                (from, to)
-            }))
-            .flat_map(|(from, goals)| {
-                algo::dijkstra(&graph, from, goals, edge_cost)
-                    .into_iter()
-                    .filter_map(move |(to, opt)| {
-                        opt.and_then(|(cost, path)| Some(((from, to), (cost, path))))
-                    })
-            })
-            .collect();
-
-    // shortest_path_descriptions
-    //     .iter()
-    //     .for_each(|desc| println!("{:?}", desc));
-
-    // we build a goal graph, where nodes are the goals and
-    // edge weights are total path cost in source graph
-    let goal_graph = graphmap::DiGraphMap::<i32, f32>::from_edges(
-        shortest_path_descriptions
-            .iter()
-            .map(|(&(from, to), (w, _))| (from, to, w)),
-    );
-
-    return algo::shortest_hamiltonian_path(&goal_graph, from_node, |e| *e.weight()).and_then(
-        |(shortest_goal_path, total_cost)| {
-            if total_cost.is_infinite() {
-                None
-            } else {
-                Some((
-                    shortest_goal_path
-                        .iter()
-                        .zip(shortest_goal_path.iter().skip(1))
-                        .map(|(&from_goal, &to_goal)| {
-                            let (_, sub_path) = shortest_path_descriptions
-                                .get(&(from_goal, to_goal))
-                                .unwrap();
-                            (
-                                to_goal,
-                                sub_path
-                                    .iter()
-                                    .zip(sub_path.iter().skip(1))
-                                    .map(|(&from, &to)| *graph.edge_weight(from, to).unwrap())
-                                    .collect(),
-                            )
-                        })
-                        .collect(),
-                    total_cost,
-                ))
-            }
-        },
-    );
-}
@@ -2,6 +2,7 @@ use std::fmt::{Debug, Display};
 use std::fs::File;
 
 use graphbash::*;
+use petgraph::graphmap;
 
 fn print_code<D, T>(destination: D, path: &[T]) where D: Debug, T: Display {
     println!("path to {:?} in {} steps:", destination, path.len());
@@ -11,15 +12,52 @@ fn print_code<D, T>(destination: D, path: &[T]) where D: Debug, T: Display {
     );
 }
 
+fn print_timing(code: &[Directions]) {
+    let timing = FrameTiming::default().code_timing(code);
+    println!(
+        "input time: {} frames ({:.2}s NTSC, {:.2}s PAL)",
+        timing.frames,
+        timing.ntsc_seconds(),
+        timing.pal_seconds()
+    );
+}
+
+fn print_failure(graph: &graphmap::DiGraphMap<i32, Directions>, from_node: i32, goals: &[Goal]) {
+    let unreachable_goals = diagnose_unreachable_goals(graph, from_node, goals);
+    if unreachable_goals.is_empty() {
+        println!("no path found: every goal is reachable, but not all in one code ending safely");
+    }
+
+    for goal in unreachable_goals {
+        match goal.reason {
+            Unreachability::OutsideDepth => {
+                println!("{} is unreachable within the generated depth", goal.index)
+            }
+            Unreachability::BlockedByHazards(hazards) => {
+                println!("{} is blocked by hazards: {:?}", goal.index, hazards)
+            }
+            Unreachability::Unreachable => println!("{} is unreachable", goal.index),
+        }
+        if let Some(nearest) = goal.nearest_reachable {
+            println!(
+                "  nearest reachable index: {} (crossing {:?} to the goal)",
+                nearest.index, nearest.crossed_hazards
+            );
+        }
+    }
+}
+
 fn main() {
     let ram_dump_filename = "resources/RAM.bin";
     let mut file = File::open(ram_dump_filename).expect("could not open RAM dump file");
 
     let depth = 50;
-    let graph = generate(&mut file, depth).expect(&format!(
-        "could not generate graph of depth {} from RAM dump file",
-        depth
-    ));
+    let graph = generate(&mut file, depth).unwrap_or_else(|_| {
+        panic!(
+            "could not generate graph of depth {} from RAM dump file",
+            depth
+        )
+    });
 
     println!(
         "graph contains {} nodes and {} edges",
@@ -41,36 +79,53 @@ fn main() {
     const VISUAL_SPINNING_SKYBOX_NODE: i32 = -989;
     const VISUAL_CORRUPT_PAUSE_NODE: i32 = -1482;
 
-    if let Some((code_segments, _)) = get_shortest_code(&graph, ROOT_INDEX, &[
-        PANIC_DASH_NODE,
-        INSTABOSS_NODE,
-        EARLY_START_NODE,
-        INSTAWIN_NODE,
-        // TIME_SKIP_NODE,
-        TIME_CUT_NODE,
-        MELT_PANIC_ARENA_SIZE_NODE,
-        // VISUAL_NO_RULES_BOX_NODE,
-        VISUAL_SPINNING_SKYBOX_NODE,
-        VISUAL_CORRUPT_PAUSE_NODE,
-    ]) {
+    // Visual effects are nice to have, but not worth more than a few extra inputs.
+    const VISUAL_REWARD: f32 = 6.0;
+
+    let goals = [
+        Goal::Required(PANIC_DASH_NODE),
+        Goal::Required(INSTABOSS_NODE),
+        Goal::Required(EARLY_START_NODE),
+        Goal::Required(INSTAWIN_NODE),
+        // Goal::Required(TIME_SKIP_NODE),
+        Goal::Required(TIME_CUT_NODE),
+        Goal::Required(MELT_PANIC_ARENA_SIZE_NODE),
+        // Goal::Optional { index: VISUAL_NO_RULES_BOX_NODE, reward: VISUAL_REWARD },
+        Goal::Optional { index: VISUAL_SPINNING_SKYBOX_NODE, reward: VISUAL_REWARD },
+        Goal::Optional { index: VISUAL_CORRUPT_PAUSE_NODE, reward: VISUAL_REWARD },
+    ];
+
+    let end = EndCondition::safe_exit();
+    let cost_model = CostProfile::rta();
+
+    if let Some(ShortestCode { code_segments, .. }) =
+        get_shortest_code(&graph, ROOT_INDEX, &goals, &end, &cost_model)
+    {
         code_segments.iter().for_each(|(goal, seg)| print_code(goal, seg));
         let (goal_order, sub_codes): (Vec<_>, Vec<_>) = code_segments.into_iter().unzip();
         let code: Vec<_> = sub_codes.into_iter().flatten().collect();
 
         println!("== all in one ==");
         print_code(&goal_order[..], &code[..]);
+        print_timing(&code);
     } else {
-        println!("no path found");
+        print_failure(&graph, ROOT_INDEX, &goals);
     }
 
-    if let Some((code_segments, cost)) = get_shortest_code(&graph, ROOT_INDEX, &[-72]) {
+    if let Some(ShortestCode {
+        code_segments,
+        cost,
+        ..
+    }) = get_shortest_code(&graph, ROOT_INDEX, &[Goal::Required(-72)], &end, &cost_model)
+    {
         code_segments.iter().for_each(|(goal, seg)| print_code(goal, seg));
         let (goal_order, sub_codes): (Vec<_>, Vec<_>) = code_segments.into_iter().unzip();
         let code: Vec<_> = sub_codes.into_iter().flatten().collect();
 
         println!("== all in one (total cost: {}) ==", cost);
         print_code(&goal_order[..], &code[..]);
+        print_timing(&code);
     } else {
-        println!("no path found");
+        print_failure(&graph, ROOT_INDEX, &[Goal::Required(-72)]);
     }
 }
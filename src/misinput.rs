@@ -0,0 +1,197 @@
+use petgraph::graphmap;
+
+use crate::cost::{held_directions, input_rule, pressed, CostModel, CostRule, HazardPenalties};
+use crate::{Directions, Goal, Hazard};
+
+/// How likely each kind of input is to register as intended. A diagonal fails when one of its axes
+/// registers a frame early, which makes it a press of that straight direction alone. The defaults
+/// are rough estimates, in line with the weights of the RTA profile.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InputReliability {
+    pub straight: f32,
+    pub diagonal: f32,
+    pub real_time_impossible: f32,
+}
+
+impl Default for InputReliability {
+    fn default() -> InputReliability {
+        InputReliability {
+            straight: 0.99,
+            diagonal: 0.95,
+            real_time_impossible: 0.85,
+        }
+    }
+}
+
+impl InputReliability {
+    pub fn success(&self, directions: Directions) -> f32 {
+        match input_rule(directions) {
+            CostRule::Straight => self.straight,
+            CostRule::Diagonal => self.diagonal,
+            _ => self.real_time_impossible,
+        }
+    }
+
+    /// The probability of every input of the code registering as intended.
+    pub fn code_success<'a, I>(&self, code: I) -> f32
+    where
+        I: IntoIterator<Item = &'a Directions>,
+    {
+        code.into_iter().fold(1.0, |success, &directions| {
+            success * self.success(directions)
+        })
+    }
+}
+
+/// Maximizes the probability of the whole code registering as intended, by minimizing the sum of
+/// -ln p over its inputs. Without hazard penalties, the cost of a code is then -ln of its success
+/// probability.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SuccessCostModel {
+    pub reliability: InputReliability,
+    pub hazards: HazardPenalties,
+}
+
+impl SuccessCostModel {
+    /// Temporary hazards cost as much as the usual penalty in straight inputs.
+    pub fn new(reliability: InputReliability) -> SuccessCostModel {
+        let straight_cost = -reliability.straight.ln();
+        let default_hazards = HazardPenalties::default();
+        SuccessCostModel {
+            reliability,
+            hazards: HazardPenalties {
+                crash: default_hazards.crash,
+                temporary: default_hazards.temporary * straight_cost,
+            },
+        }
+    }
+}
+
+impl Default for SuccessCostModel {
+    fn default() -> SuccessCostModel {
+        SuccessCostModel::new(InputReliability::default())
+    }
+}
+
+impl CostModel for SuccessCostModel {
+    fn cost(&self, _from: i32, to: i32, directions: Directions) -> f32 {
+        self.hazards.rule(to).map_or(0.0, |(_, penalty)| penalty)
+            - self.reliability.success(directions).ln()
+    }
+
+    fn rules(&self, _from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        std::iter::once(input_rule(directions))
+            .chain(self.hazards.rule(to).map(|(rule, _)| rule))
+            .collect()
+    }
+}
+
+/// What happens when a single diagonal of the code degrades to one of its straight directions,
+/// while every other input registers as intended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Misinput {
+    /// Position of the diagonal in the code.
+    pub step: usize,
+    pub pressed: Directions,
+    /// Chance of the diagonal degrading to this direction.
+    pub probability: f32,
+    /// Where the cursor goes from the index the diagonal was input on, up to the end of the code.
+    /// It stops short when the cursor leaves the generated graph or enters a crash.
+    pub cursor_path: Vec<i32>,
+    pub crashed_at: Option<i32>,
+    /// Required goals the cursor doesn't pass through anymore.
+    pub missed_goals: Vec<i32>,
+}
+
+// The straight directions a diagonal can degrade to.
+pub(crate) fn straight_components(diagonal: Directions) -> impl Iterator<Item = Directions> {
+    let held = held_directions(diagonal);
+    IntoIterator::into_iter([
+        Directions::UP,
+        Directions::LEFT,
+        Directions::RIGHT,
+        Directions::DOWN,
+    ])
+    .filter(move |&component| held.contains(component))
+}
+
+pub(crate) fn move_cursor(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from: i32,
+    pressed: Directions,
+) -> Option<i32> {
+    graph
+        .edges(from)
+        .find(|(_, _, directions)| directions.contains(pressed))
+        .map(|(_, to, _)| to)
+}
+
+// Follows the presses from `from`, stopping early on a crash or when leaving the graph.
+pub(crate) fn follow(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from: i32,
+    presses: impl Iterator<Item = Directions>,
+) -> (Vec<i32>, Option<i32>) {
+    let mut cursor_path = vec![from];
+    for pressed in presses {
+        match move_cursor(graph, *cursor_path.last().unwrap(), pressed) {
+            Some(to) => {
+                cursor_path.push(to);
+                if Hazard::of(to) == Some(Hazard::Crash) {
+                    return (cursor_path, Some(to));
+                }
+            }
+            None => break,
+        }
+    }
+    (cursor_path, None)
+}
+
+/// Simulates every way a single diagonal of the code can degrade, from the code being input on
+/// `from_node`. Each input of the code is made with one of its directions, straight ones first.
+pub fn simulate_misinputs(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    code: &[Directions],
+    goals: &[Goal],
+    reliability: &InputReliability,
+) -> Vec<Misinput> {
+    let presses: Vec<_> = code.iter().cloned().map(pressed).collect();
+    let (intended_path, _) = follow(graph, from_node, presses.iter().cloned());
+    let required_goals: Vec<_> = goals
+        .iter()
+        .filter(|goal| goal.reward().is_none())
+        .map(Goal::index)
+        .collect();
+
+    let mut misinputs = Vec::new();
+    for (step, &diagonal) in presses.iter().enumerate().take(intended_path.len() - 1) {
+        if input_rule(diagonal) != CostRule::Diagonal {
+            continue;
+        }
+
+        for component in straight_components(diagonal) {
+            let remaining_presses =
+                std::iter::once(component).chain(presses[step + 1..].iter().cloned());
+            let (cursor_path, crashed_at) = follow(graph, intended_path[step], remaining_presses);
+            let missed_goals = required_goals
+                .iter()
+                .filter(|&goal| {
+                    !intended_path[..=step].contains(goal) && !cursor_path.contains(goal)
+                })
+                .cloned()
+                .collect();
+
+            misinputs.push(Misinput {
+                step,
+                pressed: component,
+                probability: (1.0 - reliability.diagonal) / 2.0,
+                cursor_path,
+                crashed_at,
+                missed_goals,
+            });
+        }
+    }
+
+    misinputs
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::ops::Add;
+
+use petgraph::graphmap;
+
+use crate::algo::{self, Dominance};
+use crate::hazard::EntryRisk;
+use crate::route::{compare_inputs, goal_bits, required_bits, split_code, MAX_GOALS};
+use crate::utils::parallel_map;
+use crate::{CodeSegments, Directions, EndCondition, Goal, Hazard};
+
+/// The costs of a code, kept apart instead of being weighted into a single cost. Entering a hazard
+/// that doesn't crash right away counts as exposure to it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RouteCosts {
+    pub inputs: u32,
+    pub diagonals: u32,
+    pub real_time_impossible: u32,
+    pub hazard_exposure: u32,
+}
+
+impl Add for RouteCosts {
+    type Output = RouteCosts;
+
+    fn add(self, other: RouteCosts) -> RouteCosts {
+        RouteCosts {
+            inputs: self.inputs + other.inputs,
+            diagonals: self.diagonals + other.diagonals,
+            real_time_impossible: self.real_time_impossible + other.real_time_impossible,
+            hazard_exposure: self.hazard_exposure + other.hazard_exposure,
+        }
+    }
+}
+
+impl Dominance for RouteCosts {
+    fn dominates(&self, other: &RouteCosts) -> bool {
+        self != other
+            && self.inputs <= other.inputs
+            && self.diagonals <= other.diagonals
+            && self.real_time_impossible <= other.real_time_impossible
+            && self.hazard_exposure <= other.hazard_exposure
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParetoCode {
+    pub code_segments: CodeSegments,
+    pub costs: RouteCosts,
+}
+
+fn route_costs((_, to, w): (i32, i32, &Directions)) -> Option<RouteCosts> {
+    let hazard_exposure = match EntryRisk::of(to) {
+        EntryRisk::Blocked => return None,
+        EntryRisk::Temporary | EntryRisk::Conditional => 1,
+        EntryRisk::Safe => 0,
+    };
+
+    // Same as for a single cost, an edge is only as hard as the easiest input taking it.
+    Some(RouteCosts {
+        inputs: 1,
+        diagonals: (!w.has_straight() && w.has_diagonal()) as u32,
+        real_time_impossible: (!w.has_straight() && !w.has_diagonal()) as u32,
+        hazard_exposure,
+    })
+}
+
+/// Finds every code that no other code beats on all of the `RouteCosts` at once, sorted by number
+/// of inputs. Optional goals are credited when passed through, but never pursued, since their
+/// reward only makes sense against a single cost. Codes of equal costs are chosen by comparing
+/// their inputs, so that the same codes come out of every run. Nothing is returned for more than 64
+/// goals.
+pub fn get_pareto_codes(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+) -> Vec<ParetoCode> {
+    if goals.len() > MAX_GOALS {
+        return Vec::new();
+    }
+    let tie_break = |a: &[i32], b: &[i32]| compare_inputs(graph, a, b);
+    let goal_nodes: Vec<_> = goals.iter().map(Goal::index).collect();
+    let sources: Vec<_> = std::iter::once(from_node)
+        .chain(goal_nodes.iter().cloned())
+        .collect();
+
+    // Each search only depends on the graph, so they are all run in parallel.
+    let sub_paths: HashMap<_, _> = parallel_map(&sources, |&from| {
+        let to: Vec<_> = goal_nodes
+            .iter()
+            .filter(|&&to| to != from)
+            .cloned()
+            .collect();
+        algo::pareto_dijkstra(&graph, from, &to, route_costs, tie_break)
+    })
+    .into_iter()
+    .zip(sources.iter())
+    .flat_map(|(paths, &from)| {
+        paths
+            .into_iter()
+            .map(move |(to, paths)| ((from, to), paths))
+    })
+    .collect();
+
+    let can_end = |node| end.accepts(node) && Hazard::of(node) != Some(Hazard::CrashIfStopped);
+    let closing_paths: HashMap<_, _> = sources
+        .iter()
+        .map(|&node| {
+            let paths =
+                algo::pareto_dijkstra_nearest(&graph, node, can_end, route_costs, tie_break);
+            (node, paths)
+        })
+        .collect();
+
+    // Recreates the path followed by the cursor along a path in the goal graph, closing segment
+    // included when there is one.
+    let cursor_path = |goal_path: &[(i32, usize)], end_option: Option<usize>| -> Vec<i32> {
+        let (last_goal, _) = *goal_path.last().unwrap();
+        let sub_paths = goal_path
+            .iter()
+            .zip(goal_path.iter().skip(1))
+            .map(|(&(from, _), &(to, option))| &sub_paths[&(from, to)][option].1)
+            .chain(end_option.map(|end_option| &closing_paths[&last_goal][end_option].1));
+        std::iter::once(from_node)
+            .chain(sub_paths.flat_map(|sub_path| sub_path.iter().skip(1).cloned()))
+            .collect()
+    };
+
+    // the order edges are added in decides the order they are searched in
+    let mut goal_edges: Vec<_> = sub_paths.keys().cloned().collect();
+    goal_edges.sort();
+    let mut goal_graph = graphmap::DiGraphMap::<i32, ()>::new();
+    goal_graph.add_node(from_node);
+    goal_graph.extend(goal_edges);
+
+    let mut pareto_codes: Vec<_> = algo::pareto_covering_paths(
+        &goal_graph,
+        from_node,
+        goal_bits(goals, from_node),
+        required_bits(goals),
+        |(from, to, _)| {
+            sub_paths[&(from, to)]
+                .iter()
+                .map(|(costs, sub_path)| {
+                    let covered = sub_path
+                        .iter()
+                        .skip(1)
+                        .fold(0, |bits, &node| bits | goal_bits(goals, node));
+                    (*costs, covered)
+                })
+                .collect()
+        },
+        |node| {
+            closing_paths[&node]
+                .iter()
+                .map(|(costs, _)| *costs)
+                .collect()
+        },
+        |a, b| tie_break(&cursor_path(a, None), &cursor_path(b, None)),
+    )
+    .into_iter()
+    .map(|(goal_path, end_option, costs)| (costs, cursor_path(&goal_path, Some(end_option))))
+    .collect();
+
+    pareto_codes.sort_by(|(a_costs, a_path), (b_costs, b_path)| {
+        a_costs.cmp(b_costs).then_with(|| tie_break(a_path, b_path))
+    });
+    pareto_codes.dedup_by_key(|(costs, _)| *costs);
+    pareto_codes
+        .into_iter()
+        .map(|(costs, cursor_path)| ParetoCode {
+            code_segments: split_code(graph, goals, &cursor_path),
+            costs,
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+use std::ops::Range;
+
+use crate::cost::{forward_to_base, CostModel, CostRule};
+use crate::Directions;
+
+const OBJECT_ROOT_ADDRESS: i64 = 0x0BDB58;
+const OBJECT_INDEX_OFFSET: i64 = 168;
+// Offsets of the words written to when the cursor selects or deselects an object.
+const WRITTEN_OFFSETS: [i64; 3] = [0x70, 0x74, 0x7C];
+const WORD_SIZE: u32 = 4;
+
+// Main RAM is mirrored all over the address space, such as at `0x80XXXXXX`.
+const MAIN_RAM_MASK: u32 = 0x1F_FFFF;
+
+/// Addresses of the words the cursor writes to when it enters the index.
+pub fn written_words(index: i32) -> [u32; 3] {
+    let object_address = OBJECT_ROOT_ADDRESS + index as i64 * OBJECT_INDEX_OFFSET;
+    WRITTEN_OFFSETS.map(|offset| (object_address + offset) as u32)
+}
+
+/// Parts of RAM the code must not overwrite. Addresses can be given with or without the `0x80`
+/// prefix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtectedMemory {
+    ranges: Vec<Range<u32>>,
+}
+
+impl ProtectedMemory {
+    pub fn new() -> ProtectedMemory {
+        ProtectedMemory::default()
+    }
+
+    pub fn protect_address(&mut self, address: u32) {
+        let address = address & MAIN_RAM_MASK;
+        self.ranges.push(address..address + 1);
+    }
+
+    /// Protects every byte from `range.start` up to, but not including, `range.end`.
+    pub fn protect_range(&mut self, range: Range<u32>) {
+        let start = range.start & MAIN_RAM_MASK;
+        self.ranges
+            .push(start..start + range.end.saturating_sub(range.start));
+    }
+
+    pub fn is_protected(&self, address: u32) -> bool {
+        let address = address & MAIN_RAM_MASK;
+        self.ranges.iter().any(|range| range.contains(&address))
+    }
+
+    /// Whether entering the index writes to any protected byte.
+    pub fn is_touched_by(&self, index: i32) -> bool {
+        written_words(index).iter().any(|&word| {
+            let word = word & MAIN_RAM_MASK;
+            let word_end = word.saturating_add(WORD_SIZE);
+            self.ranges
+                .iter()
+                .any(|range| word < range.end && range.start < word_end)
+        })
+    }
+}
+
+/// Forbids every input entering an index that writes to protected memory. The index the code
+/// starts on has already been written to, so it is never checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProtectedMemoryCostModel<M> {
+    pub base: M,
+    pub protected: ProtectedMemory,
+}
+
+impl<M: CostModel> CostModel for ProtectedMemoryCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        if self.protected.is_touched_by(to) {
+            f32::INFINITY
+        } else {
+            self.base.cost(from, to, directions)
+        }
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        self.base.rules(from, to, directions)
+    }
+
+    fn repeat_cost(&self, from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        if self.protected.is_touched_by(to) {
+            f32::INFINITY
+        } else {
+            self.base.repeat_cost(from, to, held, first)
+        }
+    }
+
+    forward_to_base!(base: transition_cost, prices_transitions, overshoot_cost, uses_holds);
+}
@@ -0,0 +1,80 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::ops::RangeInclusive;
+
+use petgraph::graphmap;
+
+use crate::{read_moves, Directions, Hazard};
+
+/// For each index, every index moving to it along with the directions doing so.
+#[derive(Clone, Debug, Default)]
+pub struct ReverseIndex {
+    predecessors: HashMap<i32, Vec<(i32, Directions)>>,
+}
+
+impl ReverseIndex {
+    pub fn from_graph(graph: &graphmap::DiGraphMap<i32, Directions>) -> ReverseIndex {
+        let mut reverse_index = ReverseIndex::default();
+        graph
+            .all_edges()
+            .for_each(|(from, to, &directions)| reverse_index.insert(from, to, directions));
+        reverse_index
+    }
+
+    /// Reads the moves of every index in the range straight from the RAM dump, without going
+    /// through `generate`. This also finds indices that can't be reached from the name screen
+    /// itself, but fails if the range goes past the directional behavior table in the dump.
+    pub fn from_ram_dump(
+        ram_dump: &mut (impl io::Read + io::Seek),
+        indices: RangeInclusive<i32>,
+    ) -> io::Result<ReverseIndex> {
+        let mut reverse_index = ReverseIndex::default();
+        for from in indices {
+            for &(directions, to) in read_moves(ram_dump, from)?.iter() {
+                reverse_index.insert(from, to, directions);
+            }
+        }
+        Ok(reverse_index)
+    }
+
+    fn insert(&mut self, from: i32, to: i32, directions: Directions) {
+        let predecessors = self.predecessors.entry(to).or_default();
+        match predecessors.iter_mut().find(|(pred, _)| *pred == from) {
+            Some((_, known_directions)) => *known_directions |= directions,
+            None => predecessors.push((from, directions)),
+        }
+    }
+
+    pub fn predecessors(&self, index: i32) -> &[(i32, Directions)] {
+        self.predecessors
+            .get(&index)
+            .map_or(&[], |predecessors| &predecessors[..])
+    }
+}
+
+/// Finds every index from which one of the targets can be reached in at most `max_steps` inputs,
+/// along with the least number of inputs needed. Paths never go through indices that crash.
+pub fn get_reaching_indices(
+    reverse_index: &ReverseIndex,
+    targets: &[i32],
+    max_steps: usize,
+) -> HashMap<i32, usize> {
+    let mut steps: HashMap<_, _> = targets.iter().map(|&target| (target, 0)).collect();
+    let mut queue: VecDeque<_> = targets.iter().cloned().collect();
+
+    while let Some(current) = queue.pop_front() {
+        let current_steps = steps[&current];
+        if current_steps == max_steps {
+            continue;
+        }
+
+        for &(pred, _) in reverse_index.predecessors(current) {
+            if Hazard::of(pred) != Some(Hazard::Crash) && !steps.contains_key(&pred) {
+                steps.insert(pred, current_steps + 1);
+                queue.push_back(pred);
+            }
+        }
+    }
+
+    steps
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graphmap;
+
+use crate::cost::{forward_to_base, CostModel, CostRule};
+use crate::hazard::broken_inputs;
+use crate::{get_shortest_code, Directions, EndCondition, Goal, Hazard, ShortestCode};
+
+/// How to finish the code after the cursor slipped to an unplanned index.
+#[derive(Clone, Debug)]
+pub struct Recovery {
+    /// Remaining goals the slip met by writing to them, which the recovery doesn't go back to.
+    pub met_by_slip: Vec<i32>,
+    /// Hazard written to by the slip, which nothing done from here can undo.
+    pub hazard: Option<Hazard>,
+    /// Inputs the slip broke, which the recovery does without.
+    pub broken_inputs: Directions,
+    pub code: ShortestCode,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecoveryError {
+    /// The slip entered an index that crashes the name selection screen.
+    Crashed(i32),
+    /// The remaining goals can't be met from where the cursor is.
+    Unreachable,
+}
+
+impl fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecoveryError::Crashed(index) => write!(f, "the slip crashed the game on {}", index),
+            RecoveryError::Unreachable => {
+                write!(
+                    f,
+                    "the remaining goals can't be met from where the cursor is"
+                )
+            }
+        }
+    }
+}
+
+impl Error for RecoveryError {}
+
+// Restricts a cost model to the inputs a slip left working.
+struct RecoveryCostModel<'a> {
+    base: &'a dyn CostModel,
+    broken: Directions,
+}
+
+impl CostModel for RecoveryCostModel<'_> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        let available = directions - self.broken;
+        if available.is_empty() {
+            f32::INFINITY
+        } else {
+            self.base.cost(from, to, available)
+        }
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        self.base.rules(from, to, directions - self.broken)
+    }
+
+    fn repeat_cost(&self, from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        if self.broken.contains(held) {
+            f32::INFINITY
+        } else {
+            self.base.repeat_cost(from, to, held, first)
+        }
+    }
+
+    forward_to_base!(base: transition_cost, prices_transitions, overshoot_cost, uses_holds);
+}
+
+/// Plans the cheapest way to finish the code from `reached`, the index the cursor slipped to.
+/// Goals already collected are dropped from the remaining ones, so the full list of goals can be
+/// passed. The slip wrote to `reached`: it meets the goal there, and when it breaks some inputs,
+/// the recovery only uses the others.
+pub fn plan_recovery(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    reached: i32,
+    remaining: &[Goal],
+    collected: &[i32],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+) -> Result<Recovery, RecoveryError> {
+    let hazard = Hazard::of(reached);
+    if hazard == Some(Hazard::Crash) {
+        return Err(RecoveryError::Crashed(reached));
+    }
+
+    let met_by_slip = remaining
+        .iter()
+        .map(Goal::index)
+        .filter(|&index| index == reached && !collected.contains(&index))
+        .collect();
+    let goals: Vec<_> = remaining
+        .iter()
+        .filter(|goal| goal.index() != reached && !collected.contains(&goal.index()))
+        .cloned()
+        .collect();
+
+    let broken = broken_inputs(reached);
+    let recovery_cost_model = RecoveryCostModel {
+        base: cost_model,
+        broken,
+    };
+    let mut code = get_shortest_code(graph, reached, &goals, end, &recovery_cost_model)
+        .ok_or(RecoveryError::Unreachable)?;
+    // only show the inputs that still work
+    for (_, segment) in &mut code.code_segments {
+        segment.iter_mut().for_each(|input| *input -= broken);
+    }
+    for step in &mut code.steps {
+        step.input -= broken;
+    }
+
+    Ok(Recovery {
+        met_by_slip,
+        hazard,
+        broken_inputs: broken,
+        code,
+    })
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::cost::{forward_to_base, CostModel, CostRule};
+use crate::{Directions, Hazard};
+
+// The letters from A to CANCEL, and the index the cursor starts on.
+const GRID: RangeInclusive<i32> = 0..=29;
+const START_INDEX: i32 = 34;
+
+// Indices whose effects are documented in the README without being hazards.
+fn is_documented_safe(index: i32) -> bool {
+    match index {
+        -974 | -989 | -1100 | -1190 | -1399 | -1482 | -1510 | -1569 | -1608 | -1615 | -2024 => true,
+        _ => GRID.contains(&index) || index == START_INDEX,
+    }
+}
+
+/// What is known of the effect of writing to an index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IndexKnowledge {
+    KnownSafe,
+    KnownHazard,
+    Untested,
+}
+
+/// Adds a penalty to every input entering an untested index, so that routes stick to indices whose
+/// effects are known unless straying saves more than the penalty. Indices with a documented hazard
+/// are known hazards, the grid, the start index and the indices with a documented effect are known
+/// safe, and every other one is untested unless recorded otherwise in `knowledge`. Documented
+/// hazards are left to the hazard penalties of the base model, but the base model knows nothing of
+/// other known hazards, which get `known_hazard_penalty` instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiskCostModel<M> {
+    pub base: M,
+    pub knowledge: HashMap<i32, IndexKnowledge>,
+    pub untested_penalty: f32,
+    pub known_hazard_penalty: f32,
+}
+
+impl<M: CostModel> RiskCostModel<M> {
+    pub fn new(base: M, untested_penalty: f32) -> RiskCostModel<M> {
+        RiskCostModel {
+            base,
+            knowledge: HashMap::new(),
+            untested_penalty,
+            known_hazard_penalty: f32::INFINITY,
+        }
+    }
+
+    pub fn knowledge_of(&self, index: i32) -> IndexKnowledge {
+        match self.knowledge.get(&index) {
+            Some(&knowledge) => knowledge,
+            None if Hazard::of(index).is_some() => IndexKnowledge::KnownHazard,
+            None if is_documented_safe(index) => IndexKnowledge::KnownSafe,
+            None => IndexKnowledge::Untested,
+        }
+    }
+
+    /// Records the indices as known safe, such as every index a code that was tried out passed
+    /// through. Known hazards stay known hazards.
+    pub fn mark_safe(&mut self, indices: impl IntoIterator<Item = i32>) {
+        for index in indices {
+            if self.knowledge_of(index) == IndexKnowledge::Untested {
+                self.knowledge.insert(index, IndexKnowledge::KnownSafe);
+            }
+        }
+    }
+
+    fn risk(&self, index: i32) -> f32 {
+        match self.knowledge_of(index) {
+            IndexKnowledge::Untested => self.untested_penalty,
+            IndexKnowledge::KnownHazard if Hazard::of(index).is_none() => self.known_hazard_penalty,
+            IndexKnowledge::KnownSafe | IndexKnowledge::KnownHazard => 0.0,
+        }
+    }
+}
+
+impl<M: CostModel> CostModel for RiskCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        self.base.cost(from, to, directions) + self.risk(to)
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        let mut rules = self.base.rules(from, to, directions);
+        match self.knowledge_of(to) {
+            IndexKnowledge::Untested => rules.push(CostRule::UntestedIndex),
+            IndexKnowledge::KnownHazard if Hazard::of(to).is_none() => {
+                rules.push(CostRule::KnownHazard)
+            }
+            IndexKnowledge::KnownSafe | IndexKnowledge::KnownHazard => {}
+        }
+        rules
+    }
+
+    fn repeat_cost(&self, from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        self.base.repeat_cost(from, to, held, first) + self.risk(to)
+    }
+
+    forward_to_base!(base: transition_cost, prices_transitions, overshoot_cost, uses_holds);
+}
@@ -0,0 +1,693 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Sub;
+
+use petgraph::algo::Measure;
+use petgraph::graphmap;
+use petgraph::visit::EdgeRef;
+
+use crate::algo;
+use crate::cost::{pressed, CostModel};
+use crate::explain::{explain_code, Step};
+use crate::hold::{hold_end_cost, label_repeats, Action};
+use crate::misinput::InputReliability;
+use crate::oracle::DistanceOracle;
+use crate::tie_break::{tie_broken_edge_cost, TieBreak, DEFAULT_TIE_BREAKS};
+use crate::timing::{FrameTiming, Timing};
+use crate::transition::{input_state_graph, InputState};
+use crate::utils::parallel_map;
+use crate::{Directions, Hazard};
+
+// Costs the goal graph can be built with: the usual cost, possibly along with more criteria.
+pub(crate) trait RouteCost: Measure + Copy + Sub<Output = Self> + Send {
+    fn from_cost(cost: f32) -> Self;
+    fn cost(&self) -> f32;
+}
+
+impl RouteCost for f32 {
+    fn from_cost(cost: f32) -> f32 {
+        cost
+    }
+
+    fn cost(&self) -> f32 {
+        *self
+    }
+}
+
+// Orders paths by the inputs followed along them. Each edge is ranked by the first of its
+// directions, so that equivalent inputs don't matter.
+pub(crate) fn compare_inputs(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    a: &[i32],
+    b: &[i32],
+) -> Ordering {
+    let inputs = |path: &[i32]| {
+        path.iter()
+            .zip(path.iter().skip(1))
+            .map(|(&from, &to)| graph.edge_weight(from, to).unwrap().bits().trailing_zeros())
+            .collect::<Vec<_>>()
+    };
+    inputs(a).cmp(&inputs(b))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Goal {
+    Required(i32),
+    /// Only included in the code when the detour to reach it costs less than its reward.
+    Optional { index: i32, reward: f32 },
+}
+
+impl Goal {
+    pub fn index(&self) -> i32 {
+        match *self {
+            Goal::Required(index) => index,
+            Goal::Optional { index, .. } => index,
+        }
+    }
+
+    pub fn reward(&self) -> Option<f32> {
+        match *self {
+            Goal::Required(_) => None,
+            Goal::Optional { reward, .. } => Some(reward),
+        }
+    }
+
+    /// How much reaching the goal is worth when working within a budget. Required goals are worth 1
+    /// each, so that only counting goals reached is a matter of only using required goals.
+    pub fn value(&self) -> f32 {
+        self.reward().unwrap_or(1.0)
+    }
+}
+
+impl From<i32> for Goal {
+    fn from(index: i32) -> Goal {
+        Goal::Required(index)
+    }
+}
+
+/// Indices documented to crash the game on exiting the name selection screen.
+pub const EXIT_CRASH_INDICES: [i32; 4] = [-1064, -1373, -1375, -1420];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum EndCondition {
+    #[default]
+    Anywhere,
+    At(i32),
+    OneOf(Vec<i32>),
+    NotIn(Vec<i32>),
+}
+
+impl EndCondition {
+    pub fn safe_exit() -> EndCondition {
+        EndCondition::NotIn(EXIT_CRASH_INDICES.to_vec())
+    }
+
+    pub fn accepts(&self, index: i32) -> bool {
+        match self {
+            EndCondition::Anywhere => true,
+            EndCondition::At(end) => index == *end,
+            EndCondition::OneOf(ends) => ends.contains(&index),
+            EndCondition::NotIn(excluded) => !excluded.contains(&index),
+        }
+    }
+}
+
+// When the code can't end where it is, a closing segment takes the cursor to the nearest index it
+// can safely stop on. Indices that crash when stopped on may be passed through, but the code can
+// never end on them.
+pub(crate) fn can_end(end: &EndCondition) -> impl Fn(i32) -> bool + Sync + '_ {
+    move |node| end.accepts(node) && Hazard::of(node) != Some(Hazard::CrashIfStopped)
+}
+
+pub type CodeSegments = Vec<(i32, Vec<Directions>)>;
+
+// A graph where nodes are the goals and edge weights are total path cost in the cursor graph,
+// along with every goal met along the way. Each goal is tracked as a bit, so that goals passed
+// through on the way to another one are credited as well: the cursor writes to every index it
+// passes. Paths of equal cost are always chosen by comparing their inputs, so that the same
+// code comes out of every run.
+pub(crate) struct GoalGraph<'a, K> {
+    pub graph: graphmap::DiGraphMap<i32, (K, u64)>,
+    cursor_graph: &'a graphmap::DiGraphMap<i32, Directions>,
+    goals: &'a [Goal],
+    shortest_path_descriptions: HashMap<(i32, i32), (K, Vec<i32>)>,
+    closing_paths: HashMap<i32, (K, Vec<i32>)>,
+}
+
+// Goals are tracked as the bits of a `u64`, so there can be at most this many of them.
+pub(crate) const MAX_GOALS: usize = 64;
+
+// Building the goal graph gives up and returns `None` as soon as `should_stop` returns true, which
+// is checked before each node is expanded by every search, and for more than `MAX_GOALS` goals.
+impl<'a, K: RouteCost> GoalGraph<'a, K> {
+    pub fn new<F>(
+        graph: &'a graphmap::DiGraphMap<i32, Directions>,
+        from_node: i32,
+        goals: &'a [Goal],
+        end: &EndCondition,
+        edge_cost: F,
+        should_stop: &(dyn Fn() -> bool + Sync),
+    ) -> Option<GoalGraph<'a, K>>
+    where
+        F: Fn((i32, i32, &Directions)) -> K + Copy + Sync,
+    {
+        if goals.len() > MAX_GOALS {
+            return None;
+        }
+        let tie_break = |a: &[i32], b: &[i32]| compare_inputs(graph, a, b);
+        let goal_nodes: Vec<_> = goals.iter().map(Goal::index).collect();
+
+        // Each search only depends on the graph, so they are all run in parallel.
+        let searches = goal_searches(from_node, &goal_nodes);
+        let shortest_path_descriptions: HashMap<_, _> = parallel_map(&searches, |(from, goals)| {
+            algo::interruptible_dijkstra(
+                &graph,
+                &[*from],
+                goals.clone(),
+                edge_cost,
+                tie_break,
+                should_stop,
+            )
+        })
+        .into_iter()
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .zip(searches.iter())
+        .flat_map(|(descriptions, &(from, _))| {
+            descriptions
+                .into_iter()
+                .filter_map(move |(to, opt)| opt.map(|(cost, path)| ((from, to), (cost, path))))
+        })
+        .collect();
+
+        // shortest_path_descriptions
+        //     .iter()
+        //     .for_each(|desc| println!("{:?}", desc));
+
+        let can_end = can_end(end);
+        let goal_graph = GoalGraph::from_paths(
+            graph,
+            from_node,
+            goals,
+            shortest_path_descriptions,
+            |node| {
+                algo::dijkstra_nearest(
+                    &graph,
+                    node,
+                    |node| should_stop() || can_end(node),
+                    edge_cost,
+                    tie_break,
+                )
+                .filter(|(cost, _)| !cost.cost().is_infinite())
+            },
+        )?;
+        // a stopped closing search returns whatever it was expanding
+        if should_stop() {
+            return None;
+        }
+        Some(goal_graph)
+    }
+
+    /// Same as `new`, but searching with the previous input as part of the state, so that the cost
+    /// of transitions between inputs can be added, and inputs can be held to auto-repeat them. Each
+    /// segment between goals is searched as if no input came before it, which leaves out the
+    /// transitions from one segment into the next, and holds going on past a goal. Since ending on
+    /// a goal can't be priced, holds only end on one when releasing them late costs nothing.
+    pub fn with_input_states<F>(
+        graph: &'a graphmap::DiGraphMap<i32, Directions>,
+        from_node: i32,
+        goals: &'a [Goal],
+        end: &EndCondition,
+        cost_model: &dyn CostModel,
+        edge_cost: F,
+        should_stop: &(dyn Fn() -> bool + Sync),
+    ) -> Option<GoalGraph<'a, K>>
+    where
+        F: Fn((i32, i32, &Directions)) -> K + Copy + Sync,
+    {
+        if goals.len() > MAX_GOALS {
+            return None;
+        }
+        let states = input_state_graph(graph, cost_model.uses_holds());
+        let state_cost = |((from, previous, repeating), (to, next, _), &(w, repeat)): (
+            InputState,
+            InputState,
+            &(Directions, bool),
+        )| {
+            if repeat {
+                return K::from_cost(cost_model.repeat_cost(from, to, previous, !repeating));
+            }
+            let mut cost = edge_cost((from, to, &w));
+            if repeating {
+                cost = cost + K::from_cost(hold_end_cost(graph, from, previous, cost_model));
+            }
+            if previous.is_empty() || !cost_model.prices_transitions() {
+                cost
+            } else {
+                cost + K::from_cost(cost_model.transition_cost(previous, next))
+            }
+        };
+        let indices =
+            |path: &[InputState]| path.iter().map(|&(index, _, _)| index).collect::<Vec<_>>();
+        let tie_break =
+            |a: &[InputState], b: &[InputState]| compare_inputs(graph, &indices(a), &indices(b));
+
+        let can_stop = |(index, held, repeating): InputState| {
+            !repeating || hold_end_cost(graph, index, held, cost_model) == 0.0
+        };
+
+        let goal_nodes: Vec<_> = goals.iter().map(Goal::index).collect();
+        let searches = goal_searches(from_node, &goal_nodes);
+        // any state on a goal index meets it, so each search stops once the first is reached
+        let shortest_path_descriptions: HashMap<_, _> = parallel_map(&searches, |(from, goals)| {
+            algo::grouped_dijkstra(
+                &states,
+                &[(*from, Directions::empty(), false)],
+                goals,
+                |state @ (index, _, _)| Some(index).filter(|_| can_stop(state)),
+                state_cost,
+                tie_break,
+                should_stop,
+            )
+        })
+        .into_iter()
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .zip(searches.iter())
+        .flat_map(|(descriptions, &(from, _))| {
+            descriptions
+                .into_iter()
+                .map(move |(to, (cost, path))| ((from, to), (cost, indices(&path))))
+        })
+        .collect();
+
+        let can_end = can_end(end);
+        let goal_graph = GoalGraph::from_paths(
+            graph,
+            from_node,
+            goals,
+            shortest_path_descriptions,
+            |node| {
+                algo::dijkstra_nearest(
+                    &states,
+                    (node, Directions::empty(), false),
+                    |state @ (index, _, _)| should_stop() || (can_end(index) && can_stop(state)),
+                    state_cost,
+                    tie_break,
+                )
+                .filter(|(cost, _)| !cost.cost().is_infinite())
+                .map(|(cost, path)| (cost, indices(&path)))
+            },
+        )?;
+        if should_stop() {
+            return None;
+        }
+        Some(goal_graph)
+    }
+
+    /// Builds the goal graph out of the shortest paths already known between the start and goals.
+    /// `closing_path` gives the cheapest way to end the code from a node, if there is one.
+    pub fn from_paths<C>(
+        graph: &'a graphmap::DiGraphMap<i32, Directions>,
+        from_node: i32,
+        goals: &'a [Goal],
+        shortest_path_descriptions: HashMap<(i32, i32), (K, Vec<i32>)>,
+        closing_path: C,
+    ) -> Option<GoalGraph<'a, K>>
+    where
+        C: Fn(i32) -> Option<(K, Vec<i32>)> + Sync,
+    {
+        if goals.len() > MAX_GOALS {
+            return None;
+        }
+        let mut goal_graph = GoalGraph {
+            graph: graphmap::DiGraphMap::new(),
+            cursor_graph: graph,
+            goals,
+            shortest_path_descriptions,
+            closing_paths: HashMap::new(),
+        };
+
+        let mut goal_edges: Vec<_> = goal_graph
+            .shortest_path_descriptions
+            .iter()
+            .filter(|(_, (w, _))| !w.cost().is_infinite())
+            .map(|(&(from, to), (w, sub_path))| {
+                let covered = sub_path
+                    .iter()
+                    .skip(1)
+                    .fold(0, |bits, &node| bits | goal_bits(goals, node));
+                (from, to, (*w, covered))
+            })
+            .collect();
+        // the order edges are added in decides the order they are searched in
+        goal_edges.sort_by_key(|&(from, to, _)| (from, to));
+        goal_graph.graph.add_node(from_node);
+        goal_graph.graph.extend(goal_edges);
+
+        let nodes: Vec<_> = goal_graph.graph.nodes().collect();
+        goal_graph.closing_paths = parallel_map(&nodes, |&node| {
+            closing_path(node).map(|closing_path| (node, closing_path))
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Some(goal_graph)
+    }
+
+    fn covered_goals(&self, covered: u64) -> impl Iterator<Item = &Goal> + '_ {
+        self.goals
+            .iter()
+            .enumerate()
+            .filter(move |&(i, _)| covered & 1 << i != 0)
+            .map(|(_, goal)| goal)
+    }
+
+    pub fn reward(&self, covered: u64) -> K {
+        K::from_cost(
+            self.covered_goals(covered)
+                .filter_map(Goal::reward)
+                .fold(0.0, |total, reward| total + reward),
+        )
+    }
+
+    /// The total value of the goals covered, as used by budgets.
+    pub fn value(&self, covered: u64) -> f32 {
+        self.covered_goals(covered)
+            .map(Goal::value)
+            .fold(0.0, |total, value| total + value)
+    }
+
+    pub fn end_cost(&self, node: i32) -> Option<K> {
+        self.closing_paths.get(&node).map(|(cost, _)| *cost)
+    }
+
+    // The paths followed by the cursor between each goal along a path in the goal graph, closing
+    // segment included when there is one.
+    fn sub_paths<'b>(&'b self, goal_path: &'b [i32]) -> impl Iterator<Item = &'b Vec<i32>> + 'b {
+        let last_goal = *goal_path.last().unwrap();
+        goal_path
+            .iter()
+            .zip(goal_path.iter().skip(1))
+            .map(move |(&from_goal, &to_goal)| {
+                &self.shortest_path_descriptions[&(from_goal, to_goal)].1
+            })
+            .chain(self.closing_paths.get(&last_goal).map(|(_, path)| path))
+    }
+
+    /// Recreates the path followed by the cursor along a path in the goal graph.
+    pub fn cursor_path(&self, goal_path: &[i32]) -> Vec<i32> {
+        std::iter::once(goal_path[0])
+            .chain(
+                self.sub_paths(goal_path)
+                    .flat_map(|sub_path| sub_path.iter().skip(1).cloned()),
+            )
+            .collect()
+    }
+
+    /// The cost of the transitions from each segment into the next along a path in the goal graph,
+    /// which are left out of the goal graph itself.
+    pub fn boundary_transition_cost(&self, goal_path: &[i32], cost_model: &dyn CostModel) -> f32 {
+        if !cost_model.prices_transitions() {
+            return 0.0;
+        }
+        let input = |from, to| pressed(*self.cursor_graph.edge_weight(from, to).unwrap());
+        let sub_paths: Vec<_> = self
+            .sub_paths(goal_path)
+            .filter(|sub_path| sub_path.len() > 1)
+            .collect();
+        sub_paths
+            .iter()
+            .zip(sub_paths.iter().skip(1))
+            .map(|(before, after)| {
+                let previous = input(before[before.len() - 2], before[before.len() - 1]);
+                cost_model.transition_cost(previous, input(after[0], after[1]))
+            })
+            .fold(0.0, |total, cost| total + cost)
+    }
+
+    /// Which moves along a path in the goal graph are repeats of a held input.
+    pub fn repeats(&self, goal_path: &[i32], cost_model: &dyn CostModel) -> Vec<bool> {
+        if !cost_model.uses_holds() {
+            return vec![false; self.cursor_path(goal_path).len() - 1];
+        }
+        self.sub_paths(goal_path)
+            .flat_map(|sub_path| label_repeats(self.cursor_graph, sub_path, cost_model))
+            .collect()
+    }
+
+    pub fn code_segments(
+        &self,
+        graph: &graphmap::DiGraphMap<i32, Directions>,
+        goal_path: &[i32],
+    ) -> CodeSegments {
+        split_code(graph, self.goals, &self.cursor_path(goal_path))
+    }
+
+    pub fn compare_goal_paths(&self, a: &[i32], b: &[i32]) -> Ordering {
+        compare_inputs(
+            self.cursor_graph,
+            &self.cursor_path(a),
+            &self.cursor_path(b),
+        )
+    }
+}
+
+// Builds the goal graph for `cost_model`, with `edge_cost` derived from it. Searches only keep
+// track of the previous input when the model prices transitions or uses holds.
+pub(crate) fn model_goal_graph<'a, K, F>(
+    graph: &'a graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &'a [Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+    edge_cost: F,
+    should_stop: &(dyn Fn() -> bool + Sync),
+) -> Option<GoalGraph<'a, K>>
+where
+    K: RouteCost,
+    F: Fn((i32, i32, &Directions)) -> K + Copy + Sync,
+{
+    if cost_model.prices_transitions() || cost_model.uses_holds() {
+        GoalGraph::with_input_states(
+            graph,
+            from_node,
+            goals,
+            end,
+            cost_model,
+            edge_cost,
+            should_stop,
+        )
+    } else {
+        GoalGraph::new(graph, from_node, goals, end, edge_cost, should_stop)
+    }
+}
+
+// The shortest paths needed to build the goal graph: from the start to every goal, and from every
+// goal to every other one.
+fn goal_searches(from_node: i32, goal_nodes: &[i32]) -> Vec<(i32, Vec<i32>)> {
+    std::iter::once((from_node, goal_nodes.to_vec()))
+        .chain(goal_nodes.iter().map(|&from| {
+            let to = goal_nodes
+                .iter()
+                .filter(|&to| *to != from)
+                .cloned()
+                .collect::<Vec<_>>();
+            (from, to)
+        }))
+        .collect()
+}
+
+pub(crate) fn goal_bits(goals: &[Goal], node: i32) -> u64 {
+    goals
+        .iter()
+        .enumerate()
+        .filter(|(_, goal)| goal.index() == node)
+        .fold(0, |bits, (i, _)| bits | 1 << i)
+}
+
+pub(crate) fn required_bits(goals: &[Goal]) -> u64 {
+    goals
+        .iter()
+        .enumerate()
+        .filter(|(_, goal)| goal.reward().is_none())
+        .fold(0, |bits, (i, _)| bits | 1 << i)
+}
+
+// Splits the code following the cursor path wherever a goal is met for the first time. Inputs
+// leading to a goal that was already met, such as the one the code starts on, are carried over to
+// the next segment.
+pub(crate) fn split_code(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    goals: &[Goal],
+    cursor_path: &[i32],
+) -> CodeSegments {
+    let mut met_goals = vec![cursor_path[0]];
+    let mut pending_directions = Vec::new();
+    let mut code_segments = CodeSegments::new();
+
+    for (&from, &to) in cursor_path.iter().zip(cursor_path.iter().skip(1)) {
+        pending_directions.push(*graph.edge_weight(from, to).unwrap());
+        if goals.iter().any(|goal| goal.index() == to) && !met_goals.contains(&to) {
+            met_goals.push(to);
+            code_segments.push((to, std::mem::take(&mut pending_directions)));
+        }
+    }
+
+    if !pending_directions.is_empty() {
+        code_segments.push((*cursor_path.last().unwrap(), pending_directions));
+    }
+
+    code_segments
+}
+
+#[derive(Clone, Debug)]
+pub struct ShortestCode {
+    pub code_segments: CodeSegments,
+    pub cost: f32,
+    pub steps: Vec<Step>,
+}
+
+impl ShortestCode {
+    /// The code as actions, each repeat being folded into the press it continues.
+    pub fn actions(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for step in &self.steps {
+            match actions.last_mut() {
+                Some(Action::Hold { repeats, .. }) if step.repeat => *repeats += 1,
+                Some(&mut Action::Press(input)) if step.repeat => {
+                    *actions.last_mut().unwrap() = Action::Hold {
+                        input: pressed(input),
+                        repeats: 1,
+                    }
+                }
+                _ => actions.push(Action::Press(step.input)),
+            }
+        }
+        actions
+    }
+
+    pub fn timing(&self, frame_timing: &FrameTiming) -> Timing {
+        frame_timing.actions_timing(&self.actions())
+    }
+
+    pub fn success_probability(&self, reliability: &InputReliability) -> f32 {
+        reliability.code_success(self.code_segments.iter().flat_map(|(_, code)| code))
+    }
+}
+
+pub fn get_shortest_code(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+) -> Option<ShortestCode> {
+    get_shortest_code_with_tie_breaks(
+        graph,
+        from_node,
+        goals,
+        end,
+        cost_model,
+        &DEFAULT_TIE_BREAKS,
+    )
+}
+
+/// Same as `get_shortest_code`, with the criteria used to choose between codes of equal cost.
+pub fn get_shortest_code_with_tie_breaks(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+    tie_breaks: &[TieBreak],
+) -> Option<ShortestCode> {
+    let goal_graph = model_goal_graph(
+        graph,
+        from_node,
+        goals,
+        end,
+        cost_model,
+        tie_broken_edge_cost(cost_model, tie_breaks),
+        &|| false,
+    )?;
+    shortest_code(graph, &goal_graph, from_node, goals, cost_model, None)
+}
+
+/// Same as `get_shortest_code`, but every shortest path is looked up in the oracle instead of being
+/// searched for, using the costs the oracle was built with. Indices outside of the oracle can't be
+/// reached. The oracle only keeps one of the shortest paths between two indices, so among codes of
+/// equal cost, the one chosen may differ. The steps are explained with `cost_model`, which should
+/// be the one the oracle was last weighted with. Transitions between inputs can't be priced, and
+/// inputs can't be held, so nothing is returned for models doing either.
+pub fn get_shortest_code_with_oracle(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    oracle: &DistanceOracle,
+    from_node: i32,
+    goals: &[Goal],
+    end: &EndCondition,
+    cost_model: &dyn CostModel,
+) -> Option<ShortestCode> {
+    if cost_model.prices_transitions() || cost_model.uses_holds() {
+        return None;
+    }
+    let goal_nodes: Vec<_> = goals.iter().map(Goal::index).collect();
+    let shortest_path_descriptions = goal_searches(from_node, &goal_nodes)
+        .into_iter()
+        .flat_map(|(from, goals)| {
+            goals.into_iter().filter_map(move |to| {
+                let path = oracle.path(from, to)?;
+                Some(((from, to), (oracle.cost(from, to)?, path)))
+            })
+        })
+        .collect();
+
+    let can_end = can_end(end);
+    let goal_graph = GoalGraph::from_paths(
+        graph,
+        from_node,
+        goals,
+        shortest_path_descriptions,
+        |node| oracle.nearest(node, &can_end),
+    )?;
+    shortest_code(
+        graph,
+        &goal_graph,
+        from_node,
+        goals,
+        cost_model,
+        Some(oracle),
+    )
+}
+
+fn shortest_code<K: RouteCost>(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    goal_graph: &GoalGraph<K>,
+    from_node: i32,
+    goals: &[Goal],
+    cost_model: &dyn CostModel,
+    oracle: Option<&DistanceOracle>,
+) -> Option<ShortestCode> {
+    algo::shortest_covering_path(
+        &goal_graph.graph,
+        from_node,
+        goal_bits(goals, from_node),
+        required_bits(goals),
+        |e| e.weight().0,
+        |e| e.weight().1,
+        |covered| goal_graph.reward(covered),
+        |node| goal_graph.end_cost(node),
+        |a, b| goal_graph.compare_goal_paths(a, b),
+    )
+    .map(|(shortest_goal_path, total_cost)| {
+        let cursor_path = goal_graph.cursor_path(&shortest_goal_path);
+        let repeats = goal_graph.repeats(&shortest_goal_path, cost_model);
+        ShortestCode {
+            code_segments: split_code(graph, goals, &cursor_path),
+            cost: total_cost.cost()
+                + goal_graph.boundary_transition_cost(&shortest_goal_path, cost_model),
+            steps: explain_code(graph, goals, &cursor_path, &repeats, cost_model, oracle),
+        }
+    })
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use petgraph::graphmap;
+
+use crate::algo;
+use crate::cost::{
+    forward_to_base, input_rule, model_edge_cost, pressed, CostModel, CostRule, HazardPenalties,
+};
+use crate::hazard::EntryRisk;
+use crate::misinput::{follow, move_cursor, straight_components};
+use crate::route::compare_inputs;
+use crate::{Directions, Hazard};
+
+/// What happens right away when a diagonal of the code degrades to one of its straight
+/// directions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slip {
+    /// Position of the diagonal in the code.
+    pub step: usize,
+    pub pressed: Directions,
+    /// Where the diagonal should have taken the cursor.
+    pub intended: i32,
+    /// None when the slip takes the cursor out of the generated graph.
+    pub landed_on: Option<i32>,
+    pub hazard: Option<Hazard>,
+    /// The cheapest inputs taking the cursor back to the intended index, so that the rest of the
+    /// code can be input as planned. None when there is no way back.
+    pub recovery: Option<Vec<Directions>>,
+}
+
+impl Slip {
+    /// The slip lands where the diagonal would have, or somewhere safe the code can resume from.
+    pub fn is_harmless(&self) -> bool {
+        self.landed_on == Some(self.intended) || self.hazard.is_none() && self.recovery.is_some()
+    }
+}
+
+/// Finds where the cursor lands for every way each diagonal of the code can degrade, from the code
+/// being input on `from_node`, and how to recover from there with `cost_model`.
+pub fn analyze_slips(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    from_node: i32,
+    code: &[Directions],
+    cost_model: &dyn CostModel,
+) -> Vec<Slip> {
+    let presses: Vec<_> = code.iter().cloned().map(pressed).collect();
+    let (intended_path, _) = follow(graph, from_node, presses.iter().cloned());
+    let edge_cost = model_edge_cost(cost_model);
+    let tie_break = |a: &[i32], b: &[i32]| compare_inputs(graph, a, b);
+
+    let mut slips = Vec::new();
+    for (step, &diagonal) in presses.iter().enumerate().take(intended_path.len() - 1) {
+        if input_rule(diagonal) != CostRule::Diagonal {
+            continue;
+        }
+
+        let intended = intended_path[step + 1];
+        for component in straight_components(diagonal) {
+            let landed_on = move_cursor(graph, intended_path[step], component);
+            let hazard = landed_on.and_then(Hazard::of);
+            let recovery = landed_on
+                .filter(|_| hazard != Some(Hazard::Crash))
+                .and_then(|landed_on| {
+                    algo::dijkstra_nearest(
+                        graph,
+                        landed_on,
+                        |node| node == intended,
+                        edge_cost,
+                        tie_break,
+                    )
+                })
+                .filter(|(cost, _)| cost.is_finite())
+                .map(|(_, path)| {
+                    path.iter()
+                        .zip(path.iter().skip(1))
+                        .map(|(&from, &to)| *graph.edge_weight(from, to).unwrap())
+                        .collect()
+                });
+
+            slips.push(Slip {
+                step,
+                pressed: component,
+                intended,
+                landed_on,
+                hazard,
+                recovery,
+            });
+        }
+    }
+
+    slips
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SlipPenalties {
+    /// Landing on a hazard, priced as entering it would be in a cost profile.
+    pub hazards: HazardPenalties,
+    /// Landing where the intended index can't be reached back within `recovery_inputs` inputs.
+    pub unrecoverable: f32,
+    pub recovery_inputs: usize,
+}
+
+impl Default for SlipPenalties {
+    fn default() -> SlipPenalties {
+        SlipPenalties {
+            hazards: HazardPenalties {
+                crash: 20.0,
+                temporary: 5.0,
+            },
+            unrecoverable: 10.0,
+            recovery_inputs: 2,
+        }
+    }
+}
+
+/// Adds a penalty to diagonals whose slips would be harmful, so that codes where every slip is
+/// harmless or quickly recoverable are preferred. Penalties are worked out for every diagonal of
+/// the graph up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlipCostModel<M> {
+    pub base: M,
+    slip_penalties: HashMap<(i32, i32), f32>,
+}
+
+impl<M: CostModel> SlipCostModel<M> {
+    pub fn new(
+        graph: &graphmap::DiGraphMap<i32, Directions>,
+        base: M,
+        penalties: SlipPenalties,
+    ) -> SlipCostModel<M> {
+        let slip_penalties = graph
+            .all_edges()
+            .filter(|&(_, _, &directions)| input_rule(directions) == CostRule::Diagonal)
+            .map(|(from, to, &directions)| {
+                let penalty = straight_components(pressed(directions))
+                    .map(|component| {
+                        slip_penalty(graph, &penalties, to, move_cursor(graph, from, component))
+                    })
+                    .fold(0.0, |total, penalty| total + penalty);
+                ((from, to), penalty)
+            })
+            .filter(|&(_, penalty)| penalty != 0.0)
+            .collect();
+
+        SlipCostModel {
+            base,
+            slip_penalties,
+        }
+    }
+}
+
+fn slip_penalty(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    penalties: &SlipPenalties,
+    intended: i32,
+    landed_on: Option<i32>,
+) -> f32 {
+    let landed_on = match landed_on {
+        Some(landed_on) if landed_on == intended => return 0.0,
+        Some(landed_on) => landed_on,
+        None => return penalties.unrecoverable,
+    };
+
+    let hazard_penalty = match penalties.hazards.rule(landed_on) {
+        Some((CostRule::Crash, penalty)) => return penalty,
+        Some((_, penalty)) => penalty,
+        None => 0.0,
+    };
+
+    // the intended index has to be reached back without entering any crash
+    let reached = algo::reachable(graph, landed_on, Some(penalties.recovery_inputs), |node| {
+        EntryRisk::of(node) != EntryRisk::Blocked
+    });
+    let recovery_penalty = if reached.contains(&intended) {
+        0.0
+    } else {
+        penalties.unrecoverable
+    };
+    hazard_penalty + recovery_penalty
+}
+
+impl<M: CostModel> CostModel for SlipCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        self.base.cost(from, to, directions)
+            + self.slip_penalties.get(&(from, to)).cloned().unwrap_or(0.0)
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        let mut rules = self.base.rules(from, to, directions);
+        if self.slip_penalties.contains_key(&(from, to)) {
+            rules.push(CostRule::RiskySlip);
+        }
+        rules
+    }
+
+    forward_to_base!(
+        base: transition_cost,
+        prices_transitions,
+        repeat_cost,
+        overshoot_cost,
+        uses_holds,
+    );
+}
@@ -0,0 +1,104 @@
+use std::ops::{Add, Sub};
+
+use crate::cost::CostModel;
+use crate::route::RouteCost;
+use crate::{Directions, Hazard};
+
+/// Criteria used, in order, to choose between codes of equal cost. Codes that are still tied after
+/// all of them are compared input by input, in the order `Directions` are declared in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    FewerInputs,
+    FewerDiagonals,
+    /// Inputs landing on any kind of hazard, even one that is allowed.
+    FewerHazards,
+}
+
+pub const DEFAULT_TIE_BREAKS: [TieBreak; 2] = [TieBreak::FewerDiagonals, TieBreak::FewerInputs];
+
+// One per criterion, since repeating a criterion can't break any more ties.
+const MAX_TIE_BREAKS: usize = 3;
+
+impl TieBreak {
+    fn count((_, to, w): (i32, i32, &Directions)) -> impl Fn(&TieBreak) -> u32 + '_ {
+        move |tie_break| match tie_break {
+            TieBreak::FewerInputs => 1,
+            TieBreak::FewerDiagonals => (!w.has_straight() && w.has_diagonal()) as u32,
+            TieBreak::FewerHazards => Hazard::of(to).is_some() as u32,
+        }
+    }
+}
+
+// The usual cost, followed by how many times each tie break criterion was hit.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub(crate) struct TieBrokenCost {
+    cost: f32,
+    counts: [u32; MAX_TIE_BREAKS],
+}
+
+impl Add for TieBrokenCost {
+    type Output = TieBrokenCost;
+
+    fn add(mut self, other: TieBrokenCost) -> TieBrokenCost {
+        self.cost += other.cost;
+        self.counts
+            .iter_mut()
+            .zip(other.counts.iter())
+            .for_each(|(count, other)| *count += other);
+        self
+    }
+}
+
+// Only ever used to deduct rewards, which don't count towards any criterion.
+impl Sub for TieBrokenCost {
+    type Output = TieBrokenCost;
+
+    fn sub(mut self, other: TieBrokenCost) -> TieBrokenCost {
+        self.cost -= other.cost;
+        self.counts
+            .iter_mut()
+            .zip(other.counts.iter())
+            .for_each(|(count, other)| *count -= other);
+        self
+    }
+}
+
+impl RouteCost for TieBrokenCost {
+    fn from_cost(cost: f32) -> TieBrokenCost {
+        TieBrokenCost {
+            cost,
+            ..TieBrokenCost::default()
+        }
+    }
+
+    fn cost(&self) -> f32 {
+        self.cost
+    }
+}
+
+pub(crate) fn tie_broken_edge_cost<'a>(
+    cost_model: &'a dyn CostModel,
+    tie_breaks: &'a [TieBreak],
+) -> impl Fn((i32, i32, &Directions)) -> TieBrokenCost + Copy + Sync + 'a {
+    let mut criteria = [None; MAX_TIE_BREAKS];
+    let mut unique = tie_breaks
+        .iter()
+        .enumerate()
+        .filter(|&(i, tie_break)| !tie_breaks[..i].contains(tie_break))
+        .map(|(_, &tie_break)| tie_break);
+    criteria
+        .iter_mut()
+        .zip(&mut unique)
+        .for_each(|(criterion, tie_break)| *criterion = Some(tie_break));
+    move |e @ (from, to, w)| {
+        let mut counts = [0; MAX_TIE_BREAKS];
+        counts
+            .iter_mut()
+            .zip(criteria.iter().flatten().map(TieBreak::count(e)))
+            .for_each(|(count, tie_break_count)| *count = tie_break_count);
+        TieBrokenCost {
+            cost: cost_model.cost(from, to, *w),
+            counts,
+        }
+    }
+}
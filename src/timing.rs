@@ -0,0 +1,196 @@
+use crate::cost::{input_rule, CostModel, CostRule, HazardPenalties};
+use crate::{Action, Directions, Hazard};
+
+const NTSC_FRAME_RATE: f32 = 60000.0 / 1001.0;
+const PAL_FRAME_RATE: f32 = 50.0;
+
+/// How long inputs take on the name selection screen, in frames. The pad is read once per frame:
+/// a direction has to be held for `hold` frames to register, then released for `release` frames
+/// before the next one can, and presses can't follow each other faster than
+/// `min_between_presses` frames. Diagonals take `diagonal_extra` more frames for both directions
+/// to land on the same frame. Inputs are only held to auto-repeat them when `auto_repeat` is set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameTiming {
+    pub hold: u32,
+    pub release: u32,
+    pub min_between_presses: u32,
+    pub diagonal_extra: u32,
+    pub auto_repeat: Option<AutoRepeat>,
+}
+
+/// Holding a direction moves the cursor again `delay` frames after it was pressed, then every
+/// `interval` frames. The frames of the press itself then account for releasing it at the end.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AutoRepeat {
+    pub delay: u32,
+    pub interval: u32,
+}
+
+impl AutoRepeat {
+    pub fn frames(&self, first: bool) -> u32 {
+        if first {
+            self.delay
+        } else {
+            self.interval
+        }
+    }
+}
+
+impl Default for FrameTiming {
+    fn default() -> FrameTiming {
+        FrameTiming {
+            hold: 1,
+            release: 1,
+            min_between_presses: 2,
+            diagonal_extra: 1,
+            auto_repeat: None,
+        }
+    }
+}
+
+impl FrameTiming {
+    /// Frames from pressing `directions` until the next press can register.
+    pub fn frames(&self, directions: Directions) -> u32 {
+        let extra = match input_rule(directions) {
+            CostRule::Diagonal => self.diagonal_extra,
+            _ => 0,
+        };
+        (self.hold + self.release).max(self.min_between_presses) + extra
+    }
+
+    /// Frames from the first press until the last one registers, so nothing is waited for after
+    /// it.
+    pub fn code_timing<'a, I>(&self, code: I) -> Timing
+    where
+        I: IntoIterator<Item = &'a Directions>,
+    {
+        let frames: u32 = code
+            .into_iter()
+            .map(|&directions| self.frames(directions))
+            .sum();
+        self.timing_from(frames)
+    }
+
+    /// Same as `code_timing`, for codes where inputs may be held. Without auto-repeat, each repeat
+    /// of a held input is timed as pressing it again.
+    pub fn actions_timing(&self, actions: &[Action]) -> Timing {
+        let frames: u32 = actions
+            .iter()
+            .map(|action| match *action {
+                Action::Press(directions) => self.frames(directions),
+                Action::Hold { input, repeats } => {
+                    let repeat_frames = match self.auto_repeat {
+                        Some(auto_repeat) => {
+                            auto_repeat.frames(true)
+                                + auto_repeat.frames(false) * (repeats as u32).saturating_sub(1)
+                        }
+                        None => self.frames(input) * repeats as u32,
+                    };
+                    self.frames(input) + repeat_frames
+                }
+            })
+            .sum();
+        self.timing_from(frames)
+    }
+
+    // Nothing is waited for after the last input.
+    fn timing_from(&self, frames: u32) -> Timing {
+        let last_wait = (self.hold + self.release).max(self.min_between_presses) - self.hold;
+        Timing {
+            frames: frames.saturating_sub(last_wait),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timing {
+    pub frames: u32,
+}
+
+impl Timing {
+    pub fn ntsc_seconds(&self) -> f32 {
+        self.frames as f32 / NTSC_FRAME_RATE
+    }
+
+    pub fn pal_seconds(&self) -> f32 {
+        self.frames as f32 / PAL_FRAME_RATE
+    }
+}
+
+/// Minimizes the time taken to input the code, in frames. Hazard penalties are in frames as well.
+/// Inputs that can't be made on a controller are only allowed when asked for, such as for a TAS.
+/// Holds are only as safe as the index a late release would move the cursor onto, which gets the
+/// penalty of its hazard as if the cursor stopped there.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameCostModel {
+    pub timing: FrameTiming,
+    pub hazards: HazardPenalties,
+    pub allow_real_time_impossible: bool,
+}
+
+impl FrameCostModel {
+    /// Temporary hazards cost as much time as the usual penalty in straight inputs.
+    pub fn new(timing: FrameTiming) -> FrameCostModel {
+        let straight_frames = timing.frames(Directions::UP) as f32;
+        let default_hazards = HazardPenalties::default();
+        FrameCostModel {
+            timing,
+            hazards: HazardPenalties {
+                crash: default_hazards.crash,
+                temporary: default_hazards.temporary * straight_frames,
+            },
+            allow_real_time_impossible: false,
+        }
+    }
+}
+
+impl Default for FrameCostModel {
+    fn default() -> FrameCostModel {
+        FrameCostModel::new(FrameTiming::default())
+    }
+}
+
+impl CostModel for FrameCostModel {
+    fn cost(&self, _from: i32, to: i32, directions: Directions) -> f32 {
+        if input_rule(directions) == CostRule::RealTimeImpossible
+            && !self.allow_real_time_impossible
+        {
+            return f32::INFINITY;
+        }
+        self.hazards.rule(to).map_or(0.0, |(_, penalty)| penalty)
+            + self.timing.frames(directions) as f32
+    }
+
+    fn rules(&self, _from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        std::iter::once(input_rule(directions))
+            .chain(self.hazards.rule(to).map(|(rule, _)| rule))
+            .collect()
+    }
+
+    fn repeat_cost(&self, _from: i32, to: i32, held: Directions, first: bool) -> f32 {
+        match self.timing.auto_repeat {
+            Some(auto_repeat)
+                if self.allow_real_time_impossible
+                    || input_rule(held) != CostRule::RealTimeImpossible =>
+            {
+                self.hazards.rule(to).map_or(0.0, |(_, penalty)| penalty)
+                    + auto_repeat.frames(first) as f32
+            }
+            _ => f32::INFINITY,
+        }
+    }
+
+    fn overshoot_cost(&self, _at: i32, overshoot: i32) -> f32 {
+        match Hazard::of(overshoot) {
+            Some(Hazard::CrashIfStopped) => self.hazards.crash,
+            _ => self
+                .hazards
+                .rule(overshoot)
+                .map_or(0.0, |(_, penalty)| penalty),
+        }
+    }
+
+    fn uses_holds(&self) -> bool {
+        self.timing.auto_repeat.is_some()
+    }
+}
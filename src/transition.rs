@@ -0,0 +1,146 @@
+use petgraph::graphmap;
+
+use crate::cost::{forward_to_base, held_directions, pressed, CostModel, CostRule};
+use crate::misinput::move_cursor;
+use crate::Directions;
+
+const DIRECTION_COUNT: usize = 15;
+
+/// Extra cost of each input depending on the one before it, indexed by single directions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionTable {
+    costs: [[f32; DIRECTION_COUNT]; DIRECTION_COUNT],
+}
+
+fn direction_id(direction: Directions) -> usize {
+    direction.bits().trailing_zeros() as usize
+}
+
+// Where the thumb rests on the d-pad to press a single direction. Inputs made of opposite
+// directions are pressed flat from the center.
+fn thumb_position(direction: Directions) -> (f32, f32) {
+    let held = held_directions(direction);
+    let axis = |positive, negative| {
+        held.contains(positive) as i32 as f32 - held.contains(negative) as i32 as f32
+    };
+    let (x, y) = (
+        axis(Directions::RIGHT, Directions::LEFT),
+        axis(Directions::UP, Directions::DOWN),
+    );
+    let length = x.hypot(y);
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (x / length, y / length)
+    }
+}
+
+impl TransitionTable {
+    /// Prices each transition by how far the thumb moves on the d-pad, `weight` being the cost of
+    /// moving it from the center to the rim. Repeating an input is free, while going from `UR` to
+    /// `DL` crosses the whole d-pad.
+    pub fn thumb_movement(weight: f32) -> TransitionTable {
+        let mut costs = [[0.0; DIRECTION_COUNT]; DIRECTION_COUNT];
+        for (previous_id, row) in costs.iter_mut().enumerate() {
+            let previous = thumb_position(Directions::from_bits_truncate(1 << previous_id));
+            for (next_id, cost) in row.iter_mut().enumerate() {
+                let next = thumb_position(Directions::from_bits_truncate(1 << next_id));
+                *cost = weight * (next.0 - previous.0).hypot(next.1 - previous.1);
+            }
+        }
+        TransitionTable { costs }
+    }
+
+    pub fn cost(&self, previous: Directions, next: Directions) -> f32 {
+        self.costs[direction_id(previous)][direction_id(next)]
+    }
+
+    pub fn set(&mut self, previous: Directions, next: Directions, cost: f32) {
+        self.costs[direction_id(previous)][direction_id(next)] = cost;
+    }
+}
+
+impl Default for TransitionTable {
+    fn default() -> TransitionTable {
+        TransitionTable::thumb_movement(0.5)
+    }
+}
+
+/// Adds the cost of transitions between inputs to any cost model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionCostModel<M> {
+    pub base: M,
+    pub transitions: TransitionTable,
+}
+
+impl<M: CostModel> TransitionCostModel<M> {
+    pub fn new(base: M) -> TransitionCostModel<M> {
+        TransitionCostModel {
+            base,
+            transitions: TransitionTable::default(),
+        }
+    }
+}
+
+impl<M: CostModel> CostModel for TransitionCostModel<M> {
+    fn cost(&self, from: i32, to: i32, directions: Directions) -> f32 {
+        self.base.cost(from, to, directions)
+    }
+
+    fn rules(&self, from: i32, to: i32, directions: Directions) -> Vec<CostRule> {
+        self.base.rules(from, to, directions)
+    }
+
+    fn transition_cost(&self, previous: Directions, next: Directions) -> f32 {
+        self.transitions.cost(previous, next)
+    }
+
+    fn prices_transitions(&self) -> bool {
+        true
+    }
+
+    forward_to_base!(base: repeat_cost, overshoot_cost, uses_holds);
+}
+
+// A search state: the index of the cursor, the direction pressed or held to get there, which is
+// empty before the first input, and whether the cursor got there by an auto-repeat.
+pub(crate) type InputState = (i32, Directions, bool);
+
+// The cursor graph with states as nodes, so that searches know the previous input. Each edge is
+// either taken with the direction `pressed` picks out of it, or by holding the previous direction
+// when `holds` is true. Edges keep all of their directions as weight, along with whether they are
+// taken by an auto-repeat.
+pub(crate) fn input_state_graph(
+    graph: &graphmap::DiGraphMap<i32, Directions>,
+    holds: bool,
+) -> graphmap::DiGraphMap<InputState, (Directions, bool)> {
+    let mut nodes: Vec<_> = graph.nodes().collect();
+    nodes.sort_unstable();
+    let mut states = graphmap::DiGraphMap::new();
+    let mut pending: Vec<_> = nodes
+        .into_iter()
+        .map(|node| states.add_node((node, Directions::empty(), false)))
+        .collect();
+
+    while let Some(state) = pending.pop() {
+        let (from, held, _) = state;
+        let mut moves: Vec<_> = graph
+            .edges(from)
+            .map(|(_, to, &directions)| ((to, pressed(directions), false), (directions, false)))
+            .collect();
+        if holds && !held.is_empty() {
+            if let Some(to) = move_cursor(graph, from, held) {
+                let directions = *graph.edge_weight(from, to).unwrap();
+                moves.push(((to, held, true), (directions, true)));
+            }
+        }
+
+        for (next, weight) in moves {
+            if !states.contains_node(next) {
+                pending.push(states.add_node(next));
+            }
+            states.add_edge(state, next, weight);
+        }
+    }
+    states
+}
@@ -1,3 +1,7 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
 pub struct OrElse<It1, It2> {
     primary: It1,
     fallback: It2,
@@ -42,3 +46,56 @@ pub trait ItWithFallback: Iterator {
 }
 
 impl<T> ItWithFallback for T where T: Iterator {}
+
+thread_local! {
+    // Set on the threads spawned by `parallel_map`, which already keep every core busy.
+    static IN_WORKER: Cell<bool> = const { Cell::new(false) };
+}
+
+// Maps every item on as many threads as there are cores, keeping the results in order. Calls
+// nested in another `parallel_map` run on the thread they are called from instead, so that
+// threads aren't spawned for every item on top of the ones already running.
+pub fn parallel_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, |workers| workers.get())
+        .min(items.len());
+    if workers <= 1 || IN_WORKER.with(Cell::get) {
+        return items.iter().map(f).collect();
+    }
+
+    let next_item = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    IN_WORKER.with(|in_worker| in_worker.set(true));
+                    let mut worker_results = Vec::new();
+                    loop {
+                        let i = next_item.fetch_add(1, Ordering::Relaxed);
+                        if i >= items.len() {
+                            return worker_results;
+                        }
+                        worker_results.push((i, f(&items[i])));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let worker_results = handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (i, result) in worker_results {
+                results[i] = Some(result);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
+}
@@ -0,0 +1,29 @@
+use std::fs::File;
+use std::time::Instant;
+
+use graphbash::*;
+
+#[test]
+fn cancelled_search_stops_while_building_the_goal_graph() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals: Vec<_> = [-1190, -1399, -1510, -1569, -1615, -2024]
+        .iter()
+        .map(|&index| Goal::Required(index))
+        .collect();
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+
+    let code = get_shortest_code_anytime(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &CostProfile::rta(),
+        Some(Instant::now()),
+        &cancellation,
+        |_| {},
+    );
+    // a greedy code would be returned had the goal graph been built
+    assert!(code.is_none());
+}
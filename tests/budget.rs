@@ -0,0 +1,28 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn input_budget_prefers_cheaper_codes_of_equal_length() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals: Vec<_> = [-1190, -1399, -1510, -1569, -1615, -2024]
+        .iter()
+        .map(|&index| Goal::Required(index))
+        .collect();
+
+    let budgeted_codes = get_budgeted_codes(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &CostProfile::rta(),
+        Budget::Inputs(12),
+    );
+    let costs: Vec<_> = budgeted_codes
+        .iter()
+        .map(|budgeted_code| (budgeted_code.inputs, budgeted_code.cost))
+        .collect();
+    // the cheapest of the 9-input codes reaching a goal, where any of them used to be taken
+    assert_eq!(costs, [(0, 0.0), (9, 66.0)]);
+}
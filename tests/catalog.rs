@@ -0,0 +1,38 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::File;
+
+use graphbash::*;
+
+fn visit_sets(catalog: &CodeCatalog) -> HashSet<(i32, BTreeSet<i32>)> {
+    catalog
+        .iter()
+        .flatten()
+        .map(|entry| (entry.end, entry.visited.clone()))
+        .collect()
+}
+
+#[test]
+fn exact_catalog_lists_dominated_visit_sets() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+
+    let exact = visit_sets(&get_code_catalog(&graph, 34, 5, VisitDominance::Exact));
+    let fewer_visits = visit_sets(&get_code_catalog(
+        &graph,
+        34,
+        5,
+        VisitDominance::FewerVisits,
+    ));
+    let more_visits = visit_sets(&get_code_catalog(&graph, 34, 5, VisitDominance::MoreVisits));
+
+    assert!(fewer_visits.is_subset(&exact));
+    assert!(more_visits.is_subset(&exact));
+    // visit-sets avoiding some of the indices of another one are only left out when keeping more
+    assert!(exact.iter().any(|(end, visited)| {
+        !more_visits.contains(&(*end, visited.clone()))
+            && more_visits
+                .iter()
+                .any(|(other_end, other)| other_end == end && other.is_superset(visited))
+    }));
+    assert!(fewer_visits.len() < exact.len());
+}
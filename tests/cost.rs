@@ -0,0 +1,73 @@
+use std::io::ErrorKind;
+
+use graphbash::*;
+
+#[test]
+fn profile_files_override_their_base() {
+    let profile: CostProfile = r#"
+        base = "tas"
+        [directions]
+        diagonal = 2.0
+        [hazards]
+        temporary = inf
+        [[overrides]]
+        index = -1347
+        penalty = 3.0
+    "#
+    .parse()
+    .unwrap();
+
+    let mut expected = CostProfile::tas();
+    expected.directions.diagonal = 2.0;
+    expected.hazards.temporary = f32::INFINITY;
+    expected.overrides.push(IndexOverride {
+        index: -1347,
+        penalty: 3.0,
+    });
+    assert_eq!(profile, expected);
+    assert_eq!(profile.cost(-1346, -72, Directions::UP), f32::INFINITY);
+    assert_eq!(profile.cost(-1346, -1347, Directions::UP_LEFT), 5.0);
+}
+
+#[test]
+fn profile_files_start_from_rta() {
+    let profile: CostProfile = "".parse().unwrap();
+    assert_eq!(profile, CostProfile::rta());
+
+    let profile: CostProfile = "[hazards]\ncrash = 100.0".parse().unwrap();
+    assert_eq!(profile.directions, CostProfile::rta().directions);
+    assert_eq!(profile.hazards.crash, 100.0);
+    assert_eq!(
+        profile.hazards.temporary,
+        CostProfile::rta().hazards.temporary
+    );
+}
+
+#[test]
+fn unknown_base_profiles_are_rejected() {
+    let error = "base = \"speedrun\"".parse::<CostProfile>().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("unknown base profile `speedrun`"));
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    assert!("diagonal = 2.0".parse::<CostProfile>().is_err());
+    assert!("[directions]\ndiagonals = 2.0"
+        .parse::<CostProfile>()
+        .is_err());
+    assert!("[hazards]\nsoftlock = 1.0".parse::<CostProfile>().is_err());
+    assert!("[[overrides]]\nindex = -72\npenalty = 1.0\nhazard = 2.0"
+        .parse::<CostProfile>()
+        .is_err());
+}
+
+#[test]
+fn unreadable_profile_files_are_invalid_data() {
+    let path = std::env::temp_dir().join("graphbash-invalid-profile.toml");
+    std::fs::write(&path, "base = \"speedrun\"").unwrap();
+    let error = CostProfile::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
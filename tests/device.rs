@@ -0,0 +1,59 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn analog_sticks_have_their_own_combos() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(-5)];
+    let route = |device: DeviceProfile| {
+        get_shortest_code_for_device(
+            &graph,
+            34,
+            &goals,
+            &EndCondition::Anywhere,
+            &CostProfile::rta(),
+            device,
+        )
+    };
+
+    assert!(route(Device::DPad.into()).is_ok());
+    match route(Device::AnalogAsDPad.into()) {
+        Err(DeviceRouteError::Unsupported(unsupported)) => {
+            assert_eq!(unsupported.device, Device::AnalogAsDPad);
+            assert!(unsupported.input.intersects(Directions::ANY_DIAGONAL));
+        }
+        result => panic!("expected an unsupported input, got {:?}", result),
+    }
+
+    let mut calibrated = DeviceProfile::from(Device::AnalogAsDPad);
+    calibrated.combos |= Directions::ANY_DIAGONAL;
+    assert!(route(calibrated).is_ok());
+}
+
+#[test]
+fn unsupported_inputs_are_reported_when_the_base_model_forbids_them() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(-12)];
+    let mut cost_model = CostProfile::rta();
+    cost_model.directions.real_time_impossible = f32::INFINITY;
+
+    // used to be reported as unreachable, since no code was found with the base model either
+    let result = get_shortest_code_for_device(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &cost_model,
+        Device::DPad,
+    );
+    match result {
+        Err(DeviceRouteError::Unsupported(unsupported)) => {
+            assert_eq!(unsupported.device, Device::DPad);
+            assert!(!unsupported.input.intersects(Device::DPad.default_combos()));
+        }
+        result => panic!("expected an unsupported input, got {:?}", result),
+    }
+}
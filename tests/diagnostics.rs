@@ -0,0 +1,54 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn goals_blocked_by_crashes_suggest_the_nearest_reachable_index() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(18), Goal::Required(-4308)];
+
+    let unreachable_goals = diagnose_unreachable_goals(&graph, 34, &goals);
+    assert_eq!(unreachable_goals.len(), 1);
+    let goal = &unreachable_goals[0];
+    assert_eq!(goal.index, -4308);
+    assert_eq!(goal.reason, Unreachability::BlockedByHazards(vec![-2280]));
+
+    // used to be the reachable index with the closest number, however far it was in the graph
+    let nearest = goal.nearest_reachable.as_ref().unwrap();
+    assert_eq!(nearest.index, -2345);
+    assert_eq!(nearest.crossed_hazards, vec![-2280]);
+    assert!(get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(nearest.index)],
+        &EndCondition::Anywhere,
+        &CostProfile::rta()
+    )
+    .is_some());
+}
+
+#[test]
+fn only_goals_missing_from_the_graph_are_outside_its_depth() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 5).unwrap();
+    assert!(graph.contains_node(0) && !graph.contains_node(-4308));
+    let goals = [Goal::Required(0), Goal::Required(-4308)];
+
+    // -278 reaches indices left unexpanded, which used to put every goal outside the depth
+    let unreachable_goals = diagnose_unreachable_goals(&graph, -278, &goals);
+    let reasons: Vec<_> = unreachable_goals
+        .iter()
+        .map(|goal| (goal.index, goal.reason.clone()))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            (0, Unreachability::Unreachable),
+            (-4308, Unreachability::OutsideDepth)
+        ]
+    );
+    assert!(unreachable_goals
+        .iter()
+        .all(|goal| goal.nearest_reachable.is_none()));
+}
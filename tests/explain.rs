@@ -0,0 +1,46 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn steps_add_up_to_the_code() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(18), Goal::Required(-1190)];
+    let cost_model = CostProfile::rta();
+
+    let code = get_shortest_code(&graph, 34, &goals, &EndCondition::Anywhere, &cost_model).unwrap();
+    let inputs: Vec<_> = code
+        .code_segments
+        .iter()
+        .flat_map(|(_, segment)| segment.iter().cloned())
+        .collect();
+    assert_eq!(
+        code.steps.iter().map(|step| step.input).collect::<Vec<_>>(),
+        inputs
+    );
+    assert_eq!(code.steps[0].from, 34);
+
+    let mut total = 0.0;
+    for (step, next) in code.steps.iter().zip(code.steps.iter().skip(1)) {
+        assert_eq!(step.to, next.from);
+    }
+    for step in &code.steps {
+        assert_eq!(step.cost, cost_model.cost(step.from, step.to, step.input));
+        assert_eq!(step.rules, cost_model.rules(step.from, step.to, step.input));
+        total += step.cost;
+        assert_eq!(step.total, total);
+        if let Some(alternative) = &step.cheapest_alternative {
+            assert_ne!(alternative.to, step.to);
+            assert!(
+                alternative.cost + alternative.cost_to_next_goal
+                    >= step.cost + step.cost_to_next_goal
+            );
+        }
+    }
+    assert_eq!(total, code.cost);
+
+    let met: Vec<_> = code.steps.iter().filter_map(|step| step.goal).collect();
+    let segment_goals: Vec<_> = code.code_segments.iter().map(|&(goal, _)| goal).collect();
+    assert_eq!(met, segment_goals);
+}
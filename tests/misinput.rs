@@ -0,0 +1,84 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 50).unwrap()
+}
+
+fn code_of(shortest_code: &ShortestCode) -> Vec<Directions> {
+    shortest_code
+        .code_segments
+        .iter()
+        .flat_map(|(_, segment)| segment.iter().cloned())
+        .collect()
+}
+
+#[test]
+fn degraded_diagonals_follow_the_rest_of_the_code() {
+    let graph = graph();
+    let goals = [Goal::Required(1)];
+    let reliability = InputReliability::default();
+    let shortest_code = get_shortest_code(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &CostProfile::rta(),
+    )
+    .unwrap();
+    let code = code_of(&shortest_code);
+
+    let misinputs = simulate_misinputs(&graph, 34, &code, &goals, &reliability);
+    let diagonal_steps: Vec<_> = shortest_code
+        .steps
+        .iter()
+        .enumerate()
+        .filter(|(_, step)| step.rules.contains(&CostRule::Diagonal))
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(misinputs.len(), 2 * diagonal_steps.len());
+    for misinput in &misinputs {
+        assert!(diagonal_steps.contains(&misinput.step));
+        assert!(Directions::ANY_STRAIGHT.contains(misinput.pressed));
+        assert_eq!(misinput.probability, (1.0 - reliability.diagonal) / 2.0);
+        assert_eq!(
+            misinput.cursor_path[0],
+            shortest_code.steps[misinput.step].from
+        );
+        assert_eq!(misinput.cursor_path.len(), code.len() - misinput.step + 1);
+        assert_eq!(misinput.crashed_at, None);
+        assert_eq!(misinput.missed_goals, vec![1]);
+    }
+}
+
+#[test]
+fn success_model_maximizes_the_success_probability() {
+    let graph = graph();
+    let goals = [Goal::Required(-1190)];
+    let reliability = InputReliability::default();
+
+    let most_reliable = get_shortest_code(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &SuccessCostModel::new(reliability),
+    )
+    .unwrap();
+    let fewest_inputs = get_shortest_code(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &CostProfile::tas(),
+    )
+    .unwrap();
+
+    let success = most_reliable.success_probability(&reliability);
+    assert!((success - (-most_reliable.cost).exp()).abs() < 1e-6);
+    assert_eq!(success, reliability.code_success(&code_of(&most_reliable)));
+    assert!(success > fewest_inputs.success_probability(&reliability));
+}
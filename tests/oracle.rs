@@ -0,0 +1,108 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+// Shallower than the usual graph, since the oracle searches from every index.
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 5).unwrap()
+}
+
+fn assert_same_costs(oracle: &DistanceOracle, fresh: &DistanceOracle) {
+    assert_eq!(oracle.indices(), fresh.indices());
+    for &from in fresh.indices() {
+        for &to in fresh.indices() {
+            assert_eq!(
+                oracle.cost(from, to),
+                fresh.cost(from, to),
+                "{} to {}",
+                from,
+                to
+            );
+        }
+    }
+}
+
+#[test]
+fn reweighted_oracle_matches_a_fresh_one() {
+    let graph = graph();
+    let mut oracle = DistanceOracle::new(&graph, 34, &CostProfile::rta()).unwrap();
+
+    // one index gets more expensive to enter and another one cheaper, so that only some of the
+    // rows need rebuilding
+    let indices = oracle.indices().to_vec();
+    let mut cost_model = CostProfile::rta();
+    cost_model.overrides = vec![
+        IndexOverride {
+            index: indices[indices.len() / 3],
+            penalty: 10.0,
+        },
+        IndexOverride {
+            index: indices[indices.len() * 2 / 3],
+            penalty: -0.5,
+        },
+    ];
+    let rebuilt = oracle.reweight(&cost_model).unwrap();
+    assert!(0 < rebuilt && rebuilt < indices.len());
+    assert_same_costs(
+        &oracle,
+        &DistanceOracle::new(&graph, 34, &cost_model).unwrap(),
+    );
+
+    oracle.reweight(&CostProfile::tas()).unwrap();
+    assert_same_costs(
+        &oracle,
+        &DistanceOracle::new(&graph, 34, &CostProfile::tas()).unwrap(),
+    );
+}
+
+#[test]
+fn oracle_gives_up_on_transition_costs() {
+    let graph = graph();
+    let oracle = DistanceOracle::new(&graph, 34, &CostProfile::rta()).unwrap();
+    let code = get_shortest_code_with_oracle(
+        &graph,
+        &oracle,
+        34,
+        &[Goal::Required(-1190)],
+        &EndCondition::Anywhere,
+        &TransitionCostModel::new(CostProfile::rta()),
+    );
+    assert!(code.is_none());
+}
+
+#[test]
+fn negative_costs_are_rejected() {
+    let graph = graph();
+    let mut cost_model = CostProfile::rta();
+    cost_model.overrides = vec![IndexOverride {
+        index: 35,
+        penalty: -5.0,
+    }];
+    assert!(matches!(
+        DistanceOracle::new(&graph, 34, &cost_model),
+        Err(OracleError::NegativeCost { to: 35, .. })
+    ));
+
+    // the oracle is left untouched
+    let mut oracle = DistanceOracle::new(&graph, 34, &CostProfile::rta()).unwrap();
+    assert!(oracle.reweight(&cost_model).is_err());
+    assert_same_costs(
+        &oracle,
+        &DistanceOracle::new(&graph, 34, &CostProfile::rta()).unwrap(),
+    );
+}
+
+#[test]
+fn too_many_indices_are_rejected() {
+    let mut graph = graphmap::DiGraphMap::new();
+    for index in 0..u16::MAX as i32 {
+        graph.add_edge(index, index + 1, Directions::RIGHT);
+    }
+    // used to panic
+    assert!(matches!(
+        DistanceOracle::new(&graph, 0, &CostProfile::rta()),
+        Err(OracleError::TooManyIndices(_))
+    ));
+}
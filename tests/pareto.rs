@@ -0,0 +1,34 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn pareto_codes_are_the_same_on_every_run() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals: Vec<_> = [-1190, -1399, -1510]
+        .iter()
+        .map(|&index| Goal::Required(index))
+        .collect();
+
+    let codes = || -> Vec<_> {
+        get_pareto_codes(&graph, 34, &goals, &EndCondition::Anywhere)
+            .into_iter()
+            .map(|pareto_code| (pareto_code.costs, pareto_code.code_segments))
+            .collect()
+    };
+    // each run hashes with new keys, which used to change the codes chosen among equal costs
+    let first = codes();
+    for _ in 0..2 {
+        assert_eq!(codes(), first);
+    }
+}
+
+#[test]
+fn too_many_goals_give_no_codes() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals: Vec<_> = graph.nodes().take(65).map(Goal::Required).collect();
+    // used to panic, since goals are tracked as the bits of a u64
+    assert!(get_pareto_codes(&graph, 34, &goals, &EndCondition::Anywhere).is_empty());
+}
@@ -0,0 +1,32 @@
+use graphbash::*;
+
+// From the README: objects start at 0x0BDB58 and are 168 bytes apart, and selecting one writes to
+// its words at 0x70, 0x74 and 0x7C.
+fn readme_address(index: i32, offset: u32) -> u32 {
+    (0x0BDB58 + index * 168) as u32 + offset
+}
+
+#[test]
+fn written_words_follow_the_readme_offsets() {
+    for &index in &[0, 18, -974, -4308] {
+        let words = [0x70, 0x74, 0x7C].map(|offset| readme_address(index, offset));
+        assert_eq!(written_words(index), words);
+    }
+}
+
+#[test]
+fn protected_bytes_are_found_through_mirrors() {
+    let mut protected = ProtectedMemory::new();
+    protected.protect_address(0x8000_0000 | (readme_address(-974, 0x7C) + 3));
+
+    assert!(protected.is_touched_by(-974));
+    assert!(!protected.is_touched_by(-973));
+    assert!(!protected.is_touched_by(-975));
+
+    // objects below address 0 wrap around to the end of main RAM, and their words used to be
+    // compared without masking off the bits above it
+    let mut protected = ProtectedMemory::new();
+    protected.protect_range(readme_address(-5000, 0x74)..readme_address(-5000, 0x78));
+    assert!(readme_address(-5000, 0x74) > 0x8000_0000);
+    assert!(protected.is_touched_by(-5000));
+}
@@ -0,0 +1,62 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn reaching_indices_take_the_fewest_inputs() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let reverse_index = ReverseIndex::from_graph(&graph);
+
+    let reaching = get_reaching_indices(&reverse_index, &[-1190], 50);
+    assert_eq!(reaching[&-1190], 0);
+    // every input costs the same with the TAS profile, and crashes can't be entered either way
+    let code = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(-1190)],
+        &EndCondition::Anywhere,
+        &CostProfile::tas(),
+    )
+    .unwrap();
+    assert_eq!(reaching[&34], code.steps.len());
+    assert!(reaching
+        .keys()
+        .all(|&index| Hazard::of(index) != Some(Hazard::Crash)));
+
+    let nearby = get_reaching_indices(&reverse_index, &[-1190], 3);
+    assert!(nearby.values().all(|&steps| steps <= 3));
+    assert!(nearby
+        .iter()
+        .all(|(index, steps)| reaching[index] == *steps));
+}
+
+#[test]
+fn reverse_index_from_ram_dump_matches_the_graph() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let from_graph = ReverseIndex::from_graph(&graph);
+    let from_ram_dump = ReverseIndex::from_ram_dump(&mut ram_dump, -100..=100).unwrap();
+
+    // the RAM dump also knows indices the name selection screen can't reach, such as 60
+    for index in 0..=29 {
+        let from_graph: Vec<_> = from_graph
+            .predecessors(index)
+            .iter()
+            .filter(|(pred, _)| (-100..=100).contains(pred))
+            .collect();
+        let from_ram_dump = from_ram_dump.predecessors(index);
+        assert!(from_graph
+            .iter()
+            .all(|predecessor| from_ram_dump.contains(predecessor)));
+        assert!(from_ram_dump
+            .iter()
+            .all(|predecessor| from_graph.contains(&predecessor)
+                || !graph.contains_node(predecessor.0)));
+    }
+    assert!(!graph.contains_node(60));
+    assert!(from_ram_dump
+        .predecessors(0)
+        .iter()
+        .any(|&(pred, _)| pred == 60));
+}
@@ -0,0 +1,85 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 50).unwrap()
+}
+
+#[test]
+fn recoveries_do_without_the_inputs_the_slip_broke() {
+    let graph = graph();
+    let goals = [Goal::Required(-72), Goal::Required(-200)];
+    let cost_model = CostProfile::rta();
+
+    let recovery = plan_recovery(
+        &graph,
+        -72,
+        &goals,
+        &[],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    assert_eq!(recovery.met_by_slip, vec![-72]);
+    assert_eq!(recovery.hazard, Some(Hazard::Temporary));
+    assert!(recovery.broken_inputs.contains(Directions::LEFT));
+    let steps = &recovery.code.steps;
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].to, -200);
+    assert!(!steps[0].input.intersects(recovery.broken_inputs));
+
+    // -71 is only one LEFT away, which doesn't move the cursor anymore
+    let goals = [Goal::Required(-71)];
+    assert!(get_shortest_code(&graph, -72, &goals, &EndCondition::Anywhere, &cost_model).is_some());
+    let recovery = plan_recovery(
+        &graph,
+        -72,
+        &goals,
+        &[],
+        &EndCondition::Anywhere,
+        &cost_model,
+    );
+    assert_eq!(recovery.unwrap_err(), RecoveryError::Unreachable);
+}
+
+#[test]
+fn recoveries_skip_collected_goals_and_fail_on_crashes() {
+    let graph = graph();
+    let goals = [Goal::Required(18), Goal::Required(-1190)];
+    let cost_model = CostProfile::rta();
+
+    let recovery = plan_recovery(
+        &graph,
+        -63,
+        &goals,
+        &[18],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    let expected = get_shortest_code(
+        &graph,
+        -63,
+        &[Goal::Required(-1190)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    assert!(recovery.met_by_slip.is_empty());
+    assert_eq!(recovery.broken_inputs, Directions::empty());
+    assert_eq!(recovery.code.code_segments, expected.code_segments);
+    assert_eq!(recovery.code.cost, expected.cost);
+
+    let crashed = plan_recovery(
+        &graph,
+        -896,
+        &goals,
+        &[],
+        &EndCondition::Anywhere,
+        &cost_model,
+    );
+    assert_eq!(crashed.unwrap_err(), RecoveryError::Crashed(-896));
+}
@@ -0,0 +1,55 @@
+use std::fs::File;
+
+use graphbash::*;
+
+#[test]
+fn known_hazards_without_a_documented_hazard_are_avoided() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(-1190)];
+    let mut cost_model = RiskCostModel::new(CostProfile::rta(), 0.0);
+
+    let code = get_shortest_code(&graph, 34, &goals, &EndCondition::Anywhere, &cost_model).unwrap();
+    let passed = code.steps[0].to;
+    assert!(passed != -1190 && Hazard::of(passed).is_none());
+    cost_model
+        .knowledge
+        .insert(passed, IndexKnowledge::KnownHazard);
+
+    // used to cost nothing extra, since the base model has no hazard for it to price
+    let code = get_shortest_code(&graph, 34, &goals, &EndCondition::Anywhere, &cost_model).unwrap();
+    assert!(code.steps.iter().all(|step| step.to != passed));
+}
+
+#[test]
+fn documented_indices_are_known_safe() {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    let graph = generate(&mut ram_dump, 50).unwrap();
+    let goals = [Goal::Required(-1190)];
+    let cost_model = RiskCostModel::new(CostProfile::rta(), 5.0);
+    assert_eq!(cost_model.knowledge_of(34), IndexKnowledge::KnownSafe);
+    assert_eq!(cost_model.knowledge_of(0), IndexKnowledge::KnownSafe);
+
+    let code = get_shortest_code(&graph, 34, &goals, &EndCondition::Anywhere, &cost_model).unwrap();
+    // the rules box index is on the way, and neither it nor the goal used to be trusted
+    let documented: Vec<_> = code
+        .steps
+        .iter()
+        .filter(|step| step.to == -974 || step.to == -1190)
+        .collect();
+    assert_eq!(documented.len(), 2);
+    assert!(documented
+        .iter()
+        .all(|step| !step.rules.contains(&CostRule::UntestedIndex)));
+    let untested = code
+        .steps
+        .iter()
+        .filter(|step| step.rules.contains(&CostRule::UntestedIndex))
+        .count();
+    let base_cost: f32 = code
+        .steps
+        .iter()
+        .map(|step| CostProfile::rta().cost(step.from, step.to, step.input))
+        .sum();
+    assert_eq!(code.cost, base_cost + 5.0 * untested as f32);
+}
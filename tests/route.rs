@@ -0,0 +1,123 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 50).unwrap()
+}
+
+#[test]
+fn goal_on_start_index_is_met_without_inputs() {
+    let graph = graph();
+    let shortest_code = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(34)],
+        &EndCondition::Anywhere,
+        &CostProfile::rta(),
+    )
+    .unwrap();
+    assert!(shortest_code.code_segments.is_empty());
+    assert_eq!(shortest_code.cost, 0.0);
+}
+
+#[test]
+fn goal_on_start_index_is_not_collected_again() {
+    let graph = graph();
+    let cost_model = CostProfile::rta();
+    let alone = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(-1190)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    let with_start = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(34), Goal::Required(-1190)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    assert_eq!(with_start.cost, alone.cost);
+    assert_eq!(with_start.code_segments, alone.code_segments);
+}
+
+#[test]
+fn safe_exit_moves_off_exit_crash_index() {
+    let graph = graph();
+    let cost_model = CostProfile::rta();
+    let anywhere = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(-1064)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    assert_eq!(anywhere.code_segments.len(), 1);
+
+    let safe_exit = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(-1064)],
+        &EndCondition::safe_exit(),
+        &cost_model,
+    )
+    .unwrap();
+    let (last_index, _) = *safe_exit.code_segments.last().unwrap();
+    assert_eq!(safe_exit.code_segments.len(), 2);
+    assert!(!EXIT_CRASH_INDICES.contains(&last_index));
+    assert!(safe_exit.cost > anywhere.cost);
+}
+
+#[test]
+fn repeated_tie_breaks_are_ignored() {
+    let graph = graph();
+    let goals = [Goal::Required(-1190), Goal::Required(-1399)];
+    let code = |tie_breaks: &[TieBreak]| {
+        let shortest_code = get_shortest_code_with_tie_breaks(
+            &graph,
+            34,
+            &goals,
+            &EndCondition::Anywhere,
+            &CostProfile::rta(),
+            tie_breaks,
+        )
+        .unwrap();
+        (shortest_code.cost, shortest_code.code_segments)
+    };
+    let all = [
+        TieBreak::FewerHazards,
+        TieBreak::FewerDiagonals,
+        TieBreak::FewerInputs,
+    ];
+    // used to panic, since only as many criteria as there are kinds of them were supported
+    let repeated = [
+        TieBreak::FewerHazards,
+        TieBreak::FewerHazards,
+        TieBreak::FewerDiagonals,
+        TieBreak::FewerHazards,
+        TieBreak::FewerInputs,
+    ];
+    assert_eq!(code(&repeated), code(&all));
+}
+
+#[test]
+fn too_many_goals_give_no_code() {
+    let graph = graph();
+    let goals: Vec<_> = graph.nodes().take(65).map(Goal::Required).collect();
+    let shortest_code = get_shortest_code(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &CostProfile::rta(),
+    );
+    // used to panic, since goals are tracked as the bits of a u64
+    assert!(shortest_code.is_none());
+}
@@ -0,0 +1,75 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 50).unwrap()
+}
+
+fn code_of(shortest_code: &ShortestCode) -> Vec<Directions> {
+    shortest_code
+        .code_segments
+        .iter()
+        .flat_map(|(_, segment)| segment.iter().cloned())
+        .collect()
+}
+
+#[test]
+fn slips_onto_hazards_are_harmful() {
+    let graph = graph();
+    let cost_model = CostProfile::rta();
+    let shortest_code = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(1)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+
+    let slips = analyze_slips(&graph, 34, &code_of(&shortest_code), &cost_model);
+    assert_eq!(slips.len(), 2);
+    for slip in &slips {
+        let step = &shortest_code.steps[slip.step];
+        assert_eq!(slip.intended, step.to);
+        assert_ne!(slip.landed_on, Some(step.to));
+        assert_eq!(slip.hazard, slip.landed_on.and_then(Hazard::of));
+        assert!(!slip.is_harmless());
+    }
+    let onto_hazard = slips
+        .iter()
+        .find(|slip| slip.landed_on == Some(-63))
+        .unwrap();
+    assert_eq!(onto_hazard.hazard, Some(Hazard::Temporary));
+    assert!(onto_hazard.recovery.is_some());
+}
+
+#[test]
+fn slip_cost_model_prefers_harmless_slips() {
+    let graph = graph();
+    let goals = [Goal::Required(0)];
+    let cost_model = CostProfile::rta();
+    let slip_cost_model = SlipCostModel::new(&graph, cost_model.clone(), SlipPenalties::default());
+
+    let shortest_code =
+        get_shortest_code(&graph, 34, &goals, &EndCondition::Anywhere, &cost_model).unwrap();
+    let slips = analyze_slips(&graph, 34, &code_of(&shortest_code), &cost_model);
+    assert!(slips.iter().any(|slip| !slip.is_harmless()));
+
+    let robust_code = get_shortest_code(
+        &graph,
+        34,
+        &goals,
+        &EndCondition::Anywhere,
+        &slip_cost_model,
+    )
+    .unwrap();
+    let slips = analyze_slips(&graph, 34, &code_of(&robust_code), &cost_model);
+    assert!(slips.iter().all(Slip::is_harmless));
+    assert!(robust_code
+        .steps
+        .iter()
+        .all(|step| !step.rules.contains(&CostRule::RiskySlip)));
+}
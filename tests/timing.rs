@@ -0,0 +1,66 @@
+use std::fs::File;
+
+use graphbash::*;
+use petgraph::graphmap;
+
+fn graph() -> graphmap::DiGraphMap<i32, Directions> {
+    let mut ram_dump = File::open("resources/RAM.bin").unwrap();
+    generate(&mut ram_dump, 50).unwrap()
+}
+
+fn auto_repeat_timing() -> FrameTiming {
+    FrameTiming {
+        auto_repeat: Some(AutoRepeat {
+            delay: 4,
+            interval: 1,
+        }),
+        ..FrameTiming::default()
+    }
+}
+
+#[test]
+fn holds_are_timed_as_presses_without_auto_repeat() {
+    let graph = graph();
+    let cost_model = FrameCostModel::new(auto_repeat_timing());
+    let code = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(18)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    assert!(code.steps.iter().any(|step| step.repeat));
+
+    let presses: Vec<_> = code
+        .actions()
+        .into_iter()
+        .flat_map(|action| match action {
+            Action::Press(directions) => vec![directions],
+            Action::Hold { input, repeats } => vec![input; repeats + 1],
+        })
+        .collect();
+    // used to panic
+    let frame_timing = FrameTiming::default();
+    assert_eq!(
+        code.timing(&frame_timing),
+        frame_timing.code_timing(&presses)
+    );
+}
+
+#[test]
+fn holds_do_not_end_where_releasing_late_crashes() {
+    let graph = graph();
+    let cost_model = FrameCostModel::new(auto_repeat_timing());
+    let code = get_shortest_code(
+        &graph,
+        34,
+        &[Goal::Required(-1074)],
+        &EndCondition::Anywhere,
+        &cost_model,
+    )
+    .unwrap();
+    // holding on to the last input one repeat too long would move the cursor onto -1018
+    assert!(!code.steps.last().unwrap().repeat);
+    assert!(code.cost.is_finite());
+}